@@ -0,0 +1,56 @@
+//! Soulbound cw721 collection for burn certificates. Certificates are minted by the burn
+//! contract and can never leave the account they were minted to, holders may only burn them.
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError};
+use cw721::error::Cw721ContractError;
+use cw721::extension::Cw721OnchainExtensions;
+use cw721::msg::{Cw721ExecuteMsg, Cw721InstantiateMsg, Cw721QueryMsg};
+use cw721::traits::{Cw721Execute, Cw721Query};
+use cw721::{
+    DefaultOptionalCollectionExtension, DefaultOptionalCollectionExtensionMsg, DefaultOptionalNftExtension,
+    DefaultOptionalNftExtensionMsg,
+};
+
+pub const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub type InstantiateMsg = Cw721InstantiateMsg<DefaultOptionalCollectionExtensionMsg>;
+pub type ExecuteMsg = Cw721ExecuteMsg<DefaultOptionalNftExtensionMsg, DefaultOptionalCollectionExtensionMsg, Empty>;
+pub type QueryMsg = Cw721QueryMsg<DefaultOptionalNftExtension, DefaultOptionalCollectionExtension, Empty>;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, Cw721ContractError> {
+    Cw721OnchainExtensions::default().instantiate_with_version(
+        deps,
+        &env,
+        &info,
+        msg,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> Result<Response, Cw721ContractError> {
+    match msg {
+        ExecuteMsg::TransferNft { .. }
+        | ExecuteMsg::SendNft { .. }
+        | ExecuteMsg::Approve { .. }
+        | ExecuteMsg::ApproveAll { .. } => Err(Cw721ContractError::Std(StdError::generic_err(
+            "Certificates are soulbound",
+        ))),
+        msg => Cw721OnchainExtensions::default().execute(deps, &env, &info, msg),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, Cw721ContractError> {
+    Cw721OnchainExtensions::default().query(deps, &env, msg)
+}
@@ -0,0 +1,44 @@
+//! Non-transferable cw20 for burn points. Points are minted by the burn contract and stay with
+//! the account they were minted to, holders may only burn them.
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult};
+use cw2::set_contract_version;
+use cw20::Cw20ExecuteMsg;
+use cw20_base::msg::{InstantiateMsg, QueryMsg};
+use cw20_base::ContractError;
+
+pub const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub type ExecuteMsg = Cw20ExecuteMsg;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let response = cw20_base::contract::instantiate(deps.branch(), env, info, msg)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(response)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Transfer { .. }
+        | ExecuteMsg::Send { .. }
+        | ExecuteMsg::TransferFrom { .. }
+        | ExecuteMsg::SendFrom { .. }
+        | ExecuteMsg::IncreaseAllowance { .. } => Err(StdError::generic_err("Points are non-transferable").into()),
+        msg => cw20_base::contract::execute(deps, env, info, msg),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    cw20_base::contract::query(deps, env, msg)
+}
@@ -0,0 +1,100 @@
+use cosmwasm_std::{to_json_binary, Addr, Binary, Deps, DepsMut, Empty, MessageInfo, Order, Response, StdError, StdResult};
+use cw_storage_plus::Bound;
+use injective_cosmwasm::InjectiveMsgWrapper;
+
+use crate::contract::assert_admin;
+use crate::msg::SenderAccessResponse;
+use crate::state::{SenderAccess, SenderList, SENDER_ACCESS, SENDER_ALLOWLIST, SENDER_BLOCKLIST};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
+
+pub fn load_sender_access(deps: Deps) -> StdResult<SenderAccess> {
+    Ok(SENDER_ACCESS.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub fn is_sender_allowed(deps: Deps, sender: &Addr) -> StdResult<bool> {
+    Ok(match load_sender_access(deps)? {
+        SenderAccess::Open => true,
+        SenderAccess::Allowlist => SENDER_ALLOWLIST.has(deps.storage, sender),
+        SenderAccess::Blocklist => !SENDER_BLOCKLIST.has(deps.storage, sender),
+    })
+}
+
+/// Rejects burns from senders the active access mode does not let through
+pub fn assert_sender_allowed(deps: Deps, sender: &Addr) -> StdResult<()> {
+    if !is_sender_allowed(deps, sender)? {
+        return Err(StdError::generic_err(format!("Sender {} is not allowed to burn", sender)));
+    }
+    Ok(())
+}
+
+pub fn set_sender_access(
+    deps: DepsMut,
+    info: MessageInfo,
+    mode: SenderAccess,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    SENDER_ACCESS.save(deps.storage, &mode)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_sender_access")
+        .add_attribute("mode", mode.to_string()))
+}
+
+/// Adds and removes addresses on one of the lists, removals are applied last
+pub fn update_sender_list(
+    deps: DepsMut,
+    info: MessageInfo,
+    list: SenderList,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let storage = list.storage();
+    for address in add.iter() {
+        storage.save(deps.storage, &deps.api.addr_validate(address)?, &Empty {})?;
+    }
+    for address in remove.iter() {
+        storage.remove(deps.storage, &deps.api.addr_validate(address)?);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_sender_list")
+        .add_attribute("list", list.to_string())
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string()))
+}
+
+pub fn query_sender_access(deps: Deps, sender: Option<String>) -> StdResult<SenderAccessResponse> {
+    let sender_allowed = match sender {
+        Some(sender) => Some(is_sender_allowed(deps, &deps.api.addr_validate(&sender)?)?),
+        None => None,
+    };
+
+    Ok(SenderAccessResponse {
+        mode: load_sender_access(deps)?,
+        sender_allowed,
+    })
+}
+
+/// Addresses on one of the lists, ordered by address
+pub fn query_sender_list(
+    deps: Deps,
+    list: SenderList,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|address| deps.api.addr_validate(&address)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let addresses = list
+        .storage()
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&addresses)
+}
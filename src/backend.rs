@@ -0,0 +1,110 @@
+use cosmwasm_std::{AnyMsg, Addr, Coin, CosmosMsg, Deps, DepsMut, MessageInfo, Response, StdResult};
+use injective_cosmwasm::exchange::types::SubaccountId;
+use injective_cosmwasm::{InjectiveMsg, InjectiveMsgWrapper, InjectiveRoute};
+use injective_std::types::cosmos::base::v1beta1::Coin as ProtoCoin;
+use injective_std::types::injective::exchange::v1beta1::{MsgDeposit, MsgExternalTransfer, MsgWithdraw};
+
+use crate::contract::assert_admin;
+use crate::state::{MessageBackend, MESSAGE_BACKEND};
+
+pub fn load_message_backend(deps: Deps) -> StdResult<MessageBackend> {
+    Ok(MESSAGE_BACKEND.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub fn set_message_backend(
+    deps: DepsMut,
+    info: MessageInfo,
+    backend: MessageBackend,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    MESSAGE_BACKEND.save(deps.storage, &backend)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_message_backend")
+        .add_attribute("backend", backend.to_string()))
+}
+
+fn proto_coin(coin: Coin) -> Option<ProtoCoin> {
+    Some(ProtoCoin {
+        denom: coin.denom,
+        amount: coin.amount.to_string(),
+    })
+}
+
+/// Exchange messages in the encoding of the configured backend, both carry the same fields
+impl MessageBackend {
+    pub fn deposit(&self, sender: &Addr, subaccount_id: &SubaccountId, amount: Coin) -> CosmosMsg<InjectiveMsgWrapper> {
+        match self {
+            MessageBackend::Custom => CosmosMsg::Custom(InjectiveMsgWrapper {
+                route: InjectiveRoute::Exchange,
+                msg_data: InjectiveMsg::Deposit {
+                    sender: sender.clone(),
+                    subaccount_id: subaccount_id.clone(),
+                    amount,
+                },
+            }),
+            MessageBackend::Protobuf => CosmosMsg::Any(AnyMsg {
+                type_url: MsgDeposit::TYPE_URL.to_string(),
+                value: MsgDeposit {
+                    sender: sender.to_string(),
+                    subaccount_id: subaccount_id.as_str().to_string(),
+                    amount: proto_coin(amount),
+                }
+                .into(),
+            }),
+        }
+    }
+
+    pub fn withdraw(&self, sender: &Addr, subaccount_id: &SubaccountId, amount: Coin) -> CosmosMsg<InjectiveMsgWrapper> {
+        match self {
+            MessageBackend::Custom => CosmosMsg::Custom(InjectiveMsgWrapper {
+                route: InjectiveRoute::Exchange,
+                msg_data: InjectiveMsg::Withdraw {
+                    sender: sender.clone(),
+                    subaccount_id: subaccount_id.clone(),
+                    amount,
+                },
+            }),
+            MessageBackend::Protobuf => CosmosMsg::Any(AnyMsg {
+                type_url: MsgWithdraw::TYPE_URL.to_string(),
+                value: MsgWithdraw {
+                    sender: sender.to_string(),
+                    subaccount_id: subaccount_id.as_str().to_string(),
+                    amount: proto_coin(amount),
+                }
+                .into(),
+            }),
+        }
+    }
+
+    pub fn external_transfer(
+        &self,
+        sender: &Addr,
+        source_subaccount_id: &SubaccountId,
+        destination_subaccount_id: &SubaccountId,
+        amount: Coin,
+    ) -> CosmosMsg<InjectiveMsgWrapper> {
+        match self {
+            MessageBackend::Custom => CosmosMsg::Custom(InjectiveMsgWrapper {
+                route: InjectiveRoute::Exchange,
+                msg_data: InjectiveMsg::ExternalTransfer {
+                    sender: sender.clone(),
+                    source_subaccount_id: source_subaccount_id.clone(),
+                    destination_subaccount_id: destination_subaccount_id.clone(),
+                    amount,
+                },
+            }),
+            MessageBackend::Protobuf => CosmosMsg::Any(AnyMsg {
+                type_url: MsgExternalTransfer::TYPE_URL.to_string(),
+                value: MsgExternalTransfer {
+                    sender: sender.to_string(),
+                    source_subaccount_id: source_subaccount_id.as_str().to_string(),
+                    destination_subaccount_id: destination_subaccount_id.as_str().to_string(),
+                    amount: proto_coin(amount),
+                }
+                .into(),
+            }),
+        }
+    }
+}
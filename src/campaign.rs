@@ -0,0 +1,289 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, Event, MessageInfo, Order, Response, StdError, StdResult,
+    Storage, SubMsg, Uint128,
+};
+use cw_storage_plus::Bound;
+use injective_cosmwasm::InjectiveMsgWrapper;
+
+use crate::contract::{assert_admin, queue_burn, record_burn};
+use crate::escrow::{assert_native_escrow, lock_escrow, refund_escrow, release_escrow};
+use crate::msg::{CampaignMatchResponse, CampaignMinPoolResponse, CampaignResponse};
+use crate::state::{
+    campaigns, Asset, AssetInfo, BurnDetails, Campaign, CampaignStatus, CampaignTerms, CAMPAIGN_COUNT,
+    CAMPAIGN_MATCHES, CAMPAIGN_MIN_POOLS, RUNNING_CAMPAIGNS,
+};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+/// Every running campaign queues a matched burn inside the user's burn, this bounds how many
+pub const MAX_RUNNING_CAMPAIGNS: usize = 5;
+
+pub fn set_campaign_min_pool(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    amount: Option<Uint128>,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let key = asset_info.to_string();
+    match amount {
+        Some(amount) if amount.is_zero() => {
+            return Err(StdError::generic_err("Campaign minimum pool must be greater than zero"));
+        }
+        Some(amount) => CAMPAIGN_MIN_POOLS.save(deps.storage, &key, &amount)?,
+        None => CAMPAIGN_MIN_POOLS.remove(deps.storage, &key),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_campaign_min_pool")
+        .add_attribute("asset_info", key)
+        .add_attribute("min_pool", amount.map(|amount| amount.to_string()).unwrap_or("none".to_string())))
+}
+
+/// Campaigns still running on an asset. Entries of campaigns that ended or ran dry are dropped
+/// from the running index on the way.
+fn load_running_campaigns(storage: &mut dyn Storage, env: &Env, asset_key: &str) -> StdResult<Vec<Campaign>> {
+    let ids = RUNNING_CAMPAIGNS
+        .prefix(asset_key)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut running = vec![];
+    for id in ids {
+        let campaign = campaigns().load(storage, id)?;
+        if campaign.is_running(env.block.time) {
+            running.push(campaign);
+        } else {
+            RUNNING_CAMPAIGNS.remove(storage, (asset_key, id));
+        }
+    }
+    Ok(running)
+}
+
+/// Escrows a native matching pool attached as funds, exactly `pool` has to be attached
+pub fn create_native_campaign(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pool: Asset,
+    terms: CampaignTerms,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_native_escrow(&info, &pool)?;
+
+    create_campaign(deps, env, info.sender, pool, terms)
+}
+
+/// Starts a campaign matching burns of the pool's asset from a pool this contract already holds
+pub fn create_campaign(
+    deps: DepsMut,
+    env: Env,
+    sponsor: Addr,
+    pool: Asset,
+    terms: CampaignTerms,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    if pool.amount.is_zero() {
+        return Err(StdError::generic_err("Campaign pool must be greater than zero"));
+    }
+    if terms.match_ratio.is_zero() || terms.per_user_cap.is_zero() {
+        return Err(StdError::generic_err("Campaign match ratio and per-user cap must be greater than zero"));
+    }
+    if terms.end_time <= env.block.time {
+        return Err(StdError::generic_err("Campaign must end in the future"));
+    }
+    let asset_key = pool.info.to_string();
+    let min_pool = CAMPAIGN_MIN_POOLS
+        .may_load(deps.storage, &asset_key)?
+        .ok_or_else(|| StdError::generic_err(format!("Campaigns are not enabled for {}", asset_key)))?;
+    if pool.amount < min_pool {
+        return Err(StdError::generic_err(format!(
+            "Campaign pool must be at least {}",
+            min_pool
+        )));
+    }
+    if load_running_campaigns(deps.storage, &env, &asset_key)?.len() >= MAX_RUNNING_CAMPAIGNS {
+        return Err(StdError::generic_err(format!(
+            "{} already has {} running campaigns",
+            asset_key, MAX_RUNNING_CAMPAIGNS
+        )));
+    }
+
+    let id = CAMPAIGN_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    CAMPAIGN_COUNT.save(deps.storage, &id)?;
+
+    let campaign = Campaign {
+        id,
+        sponsor,
+        pool,
+        terms,
+        matched: Default::default(),
+        participants: 0,
+        refunded: Default::default(),
+        created_at: env.block.time,
+        status: CampaignStatus::Active,
+        closed_at: None,
+    };
+    campaigns().save(deps.storage, id, &campaign)?;
+    RUNNING_CAMPAIGNS.save(deps.storage, (&asset_key, id), &Empty {})?;
+    lock_escrow(deps.storage, &campaign.pool)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "create_campaign")
+        .add_attribute("campaign_id", id.to_string())
+        .add_attribute("sponsor", campaign.sponsor)
+        .add_attribute("pool", campaign.pool.to_string())
+        .add_attribute("end_time", campaign.terms.end_time.seconds().to_string()))
+}
+
+/// Matches a user burn from every running campaign on its asset. The matched amounts are burned
+/// from the pools on behalf of the sponsors, up to each user's cap.
+pub fn match_burn(
+    mut deps: DepsMut,
+    env: &Env,
+    burn: &BurnDetails,
+    messages: &mut Vec<SubMsg<InjectiveMsgWrapper>>,
+    events: &mut Vec<Event>,
+) -> StdResult<()> {
+    let asset_key = burn.asset.info.to_string();
+    let running = load_running_campaigns(deps.storage, env, &asset_key)?;
+
+    let user = &burn.beneficiary;
+    for mut campaign in running {
+        let already_matched = CAMPAIGN_MATCHES
+            .may_load(deps.storage, (campaign.id, user))?
+            .unwrap_or_default();
+        let amount = burn
+            .asset
+            .amount
+            .mul_floor(campaign.terms.match_ratio)
+            .min(campaign.terms.per_user_cap.saturating_sub(already_matched))
+            .min(campaign.remaining());
+        if amount.is_zero() {
+            continue;
+        }
+
+        if already_matched.is_zero() {
+            campaign.participants += 1;
+        }
+        campaign.matched += amount;
+        campaigns().save(deps.storage, campaign.id, &campaign)?;
+        if campaign.remaining().is_zero() {
+            RUNNING_CAMPAIGNS.remove(deps.storage, (&asset_key, campaign.id));
+        }
+        CAMPAIGN_MATCHES.save(deps.storage, (campaign.id, user), &(already_matched + amount))?;
+
+        let matched = Asset {
+            info: campaign.pool.info.clone(),
+            amount,
+        };
+        release_escrow(deps.storage, &matched)?;
+        let auction_denom =
+            queue_burn(deps.branch(), env, user, &campaign.sponsor, matched.clone(), messages)?;
+        let matched_burn = BurnDetails {
+            sender: campaign.sponsor.clone(),
+            beneficiary: campaign.sponsor,
+            asset: matched,
+            auction_denom,
+        };
+        record_burn(deps.branch(), env, &matched_burn, messages, events)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the unused pool of an ended campaign to its sponsor, callable by anyone
+pub fn close_campaign(deps: DepsMut, env: Env, id: u64) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let mut campaign = campaigns().load(deps.storage, id)?;
+    if campaign.status != CampaignStatus::Active {
+        return Err(StdError::generic_err(format!("Campaign {} is already closed", id)));
+    }
+    if env.block.time < campaign.terms.end_time {
+        return Err(StdError::generic_err(format!(
+            "Campaign {} runs until {}",
+            id,
+            campaign.terms.end_time.seconds()
+        )));
+    }
+
+    let refund = campaign.remaining();
+    campaign.refunded = refund;
+    campaign.status = CampaignStatus::Closed;
+    campaign.closed_at = Some(env.block.time);
+    campaigns().save(deps.storage, id, &campaign)?;
+    RUNNING_CAMPAIGNS.remove(deps.storage, (&campaign.pool.info.to_string(), id));
+
+    let mut response = Response::new();
+    if !refund.is_zero() {
+        let refund_asset = Asset {
+            info: campaign.pool.info.clone(),
+            amount: refund,
+        };
+        release_escrow(deps.storage, &refund_asset)?;
+        response = response.add_message(refund_escrow(refund_asset, campaign.sponsor.clone())?);
+    }
+
+    Ok(response
+        .add_attribute("action", "close_campaign")
+        .add_attribute("campaign_id", id.to_string())
+        .add_attribute("matched", campaign.matched.to_string())
+        .add_attribute("refunded", refund.to_string()))
+}
+
+fn campaign_response(env: &Env, campaign: Campaign) -> CampaignResponse {
+    CampaignResponse {
+        remaining: campaign.remaining(),
+        running: campaign.is_running(env.block.time),
+        campaign,
+    }
+}
+
+pub fn query_campaign(deps: Deps, env: Env, id: u64) -> StdResult<Binary> {
+    to_json_binary(&campaign_response(&env, campaigns().load(deps.storage, id)?))
+}
+
+/// Campaigns ordered by id, only the ones matching `asset_info` when it is given
+pub fn query_campaigns(
+    deps: Deps,
+    env: Env,
+    asset_info: Option<AssetInfo>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let range = match asset_info {
+        Some(asset_info) => campaigns().idx.asset.prefix(asset_info.to_string()).range(
+            deps.storage,
+            start,
+            None,
+            Order::Ascending,
+        ),
+        None => campaigns().range(deps.storage, start, None, Order::Ascending),
+    };
+    let list = range
+        .take(limit)
+        .map(|item| item.map(|(_, campaign)| campaign_response(&env, campaign)))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&list)
+}
+
+pub fn query_campaign_match(deps: Deps, id: u64, user: String) -> StdResult<Binary> {
+    let campaign = campaigns().load(deps.storage, id)?;
+    let user = deps.api.addr_validate(&user)?;
+    let matched = CAMPAIGN_MATCHES
+        .may_load(deps.storage, (id, &user))?
+        .unwrap_or_default();
+
+    to_json_binary(&CampaignMatchResponse {
+        matched,
+        remaining_cap: campaign.terms.per_user_cap.saturating_sub(matched),
+        match_ratio: campaign.terms.match_ratio,
+    })
+}
+
+pub fn query_campaign_min_pool(deps: Deps, asset_info: AssetInfo) -> StdResult<Binary> {
+    to_json_binary(&CampaignMinPoolResponse {
+        min_pool: CAMPAIGN_MIN_POOLS.may_load(deps.storage, &asset_info.to_string())?,
+    })
+}
@@ -0,0 +1,178 @@
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, Event, MessageInfo, Reply, Response, StdResult, SubMsg,
+    SubMsgResult, Uint128, WasmMsg,
+};
+use injective_cosmwasm::InjectiveMsgWrapper;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::assert_admin;
+use crate::msg::CertificateConfigResponse;
+use crate::querier::query_auction_round;
+use crate::state::{AssetInfo, BurnDetails, CERTIFICATE_CONTRACT, CERTIFICATE_THRESHOLDS};
+
+pub const CERTIFICATE_REPLY_ID: u64 = 5;
+
+/// Gas available to a certificate mint, running out is caught like any other failure
+pub const CERTIFICATE_GAS_LIMIT: u64 = 300_000;
+
+/// Mint message of a cw721 collection with on-chain metadata, this contract has to be its minter
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721MintMsg {
+    Mint {
+        token_id: String,
+        owner: String,
+        token_uri: Option<String>,
+        extension: Option<CertificateMetadata>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CertificateMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub attributes: Option<Vec<CertificateTrait>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CertificateTrait {
+    pub display_type: Option<String>,
+    pub trait_type: String,
+    pub value: String,
+}
+
+pub fn set_certificate_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: Option<String>,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let contract = match contract {
+        Some(contract) => {
+            let contract = deps.api.addr_validate(&contract)?.to_string();
+            CERTIFICATE_CONTRACT.save(deps.storage, &contract)?;
+            contract
+        }
+        None => {
+            CERTIFICATE_CONTRACT.remove(deps.storage);
+            "none".to_string()
+        }
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "set_certificate_contract")
+        .add_attribute("contract", contract))
+}
+
+pub fn set_certificate_threshold(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    threshold: Option<Uint128>,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let key = asset_info.to_string();
+    match threshold {
+        Some(threshold) => CERTIFICATE_THRESHOLDS.save(deps.storage, &key, &threshold)?,
+        None => CERTIFICATE_THRESHOLDS.remove(deps.storage, &key),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_certificate_threshold")
+        .add_attribute("asset", key)
+        .add_attribute(
+            "threshold",
+            threshold.map_or("none".to_string(), |threshold| threshold.to_string()),
+        ))
+}
+
+/// Mints a certificate to the beneficiary when the burn is above the threshold of its asset.
+/// Assets without a threshold never earn one. A failed mint is logged as a
+/// `certificate_mint_failed` event and never reverts the burn.
+pub fn certificate_mint_message(
+    deps: Deps,
+    env: &Env,
+    burn_id: u64,
+    burn: &BurnDetails,
+    events: &mut Vec<Event>,
+) -> StdResult<Option<SubMsg<InjectiveMsgWrapper>>> {
+    let contract = match CERTIFICATE_CONTRACT.may_load(deps.storage)? {
+        Some(contract) => contract,
+        None => return Ok(None),
+    };
+    let asset = burn.asset.info.to_string();
+    match CERTIFICATE_THRESHOLDS.may_load(deps.storage, &asset)? {
+        Some(threshold) if burn.asset.amount > threshold => {}
+        _ => return Ok(None),
+    }
+
+    // Without the auction round there is no certificate to mint, the burn goes ahead regardless
+    let round = match query_auction_round(&deps.querier) {
+        Ok(round) => round,
+        Err(error) => {
+            events.push(certificate_mint_failed_event(&burn_id.to_string(), &error.to_string()));
+            return Ok(None);
+        }
+    };
+    let attribute = |trait_type: &str, value: String| CertificateTrait {
+        display_type: None,
+        trait_type: trait_type.to_string(),
+        value,
+    };
+    let mint_msg = Cw721MintMsg::Mint {
+        token_id: burn_id.to_string(),
+        owner: burn.beneficiary.to_string(),
+        token_uri: None,
+        extension: Some(CertificateMetadata {
+            name: Some(format!("Burn certificate #{}", burn_id)),
+            description: Some(format!("Burned {} in auction round {}", burn.asset, round)),
+            attributes: Some(vec![
+                attribute("asset", asset),
+                attribute("amount", burn.asset.amount.to_string()),
+                attribute("round", round.to_string()),
+                attribute("timestamp", env.block.time.seconds().to_string()),
+            ]),
+        }),
+    };
+
+    let execute_msg = WasmMsg::Execute {
+        contract_addr: contract,
+        msg: to_json_binary(&mint_msg)?,
+        funds: vec![],
+    };
+    Ok(Some(
+        SubMsg::reply_on_error(execute_msg, CERTIFICATE_REPLY_ID)
+            .with_gas_limit(CERTIFICATE_GAS_LIMIT)
+            .with_payload(Binary::from(burn_id.to_string().as_bytes())),
+    ))
+}
+
+fn certificate_mint_failed_event(burn_id: &str, error: &str) -> Event {
+    Event::new("certificate_mint_failed")
+        .add_attribute("burn_id", burn_id)
+        .add_attribute("error", error)
+}
+
+pub fn handle_certificate_reply(msg: Reply) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let burn_id = String::from_utf8(msg.payload.to_vec())?;
+    let error = match msg.result {
+        SubMsgResult::Err(error) => error,
+        SubMsgResult::Ok(_) => return Ok(Response::new()),
+    };
+
+    Ok(Response::new().add_event(certificate_mint_failed_event(&burn_id, &error)))
+}
+
+pub fn query_certificate_config(deps: Deps) -> StdResult<CertificateConfigResponse> {
+    let thresholds = CERTIFICATE_THRESHOLDS
+        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CertificateConfigResponse {
+        contract: CERTIFICATE_CONTRACT.may_load(deps.storage)?,
+        thresholds,
+    })
+}
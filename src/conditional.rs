@@ -0,0 +1,193 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError, StdResult,
+    SubMsg,
+};
+use cw_storage_plus::Bound;
+use injective_cosmwasm::InjectiveMsgWrapper;
+use injective_math::FPDecimal;
+
+use crate::contract::burn_now;
+use crate::escrow::{assert_native_escrow, lock_escrow, refund_escrow, release_escrow};
+use crate::msg::{TriggerableConditionalBurn, TriggerableConditionalBurnsResponse};
+use crate::querier::query_oracle_price;
+use crate::state::{
+    Asset, BurnCondition, ConditionalBurn, ConditionalBurnStatus, CONDITIONAL_BURNS, CONDITIONAL_BURN_COUNT,
+    OPEN_CONDITIONAL_BURNS,
+};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// Escrows native coins attached as funds, exactly `asset` has to be attached
+pub fn create_native_conditional_burn(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    condition: BurnCondition,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_native_escrow(&info, &asset)?;
+
+    create_conditional_burn(deps, env, info.sender, asset, condition)
+}
+
+/// Records a conditional burn for an asset this contract already holds
+pub fn create_conditional_burn(
+    deps: DepsMut,
+    env: Env,
+    owner: Addr,
+    asset: Asset,
+    condition: BurnCondition,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    if asset.amount.is_zero() {
+        return Err(StdError::generic_err("Conditional burn amount must be greater than zero"));
+    }
+    if condition.threshold <= FPDecimal::ZERO {
+        return Err(StdError::generic_err("Conditional burn threshold must be positive"));
+    }
+    if condition.expires_at <= env.block.time {
+        return Err(StdError::generic_err("Conditional burn expiry must be in the future"));
+    }
+    // Fail early on a pair the oracle does not know rather than at trigger time
+    query_oracle_price(&deps.querier, &condition.source)?;
+
+    let id = CONDITIONAL_BURN_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    CONDITIONAL_BURN_COUNT.save(deps.storage, &id)?;
+
+    let order = ConditionalBurn {
+        id,
+        owner,
+        asset,
+        condition,
+        created_at: env.block.time,
+        status: ConditionalBurnStatus::Open,
+        closed_at: None,
+    };
+    CONDITIONAL_BURNS.save(deps.storage, id, &order)?;
+    OPEN_CONDITIONAL_BURNS.save(deps.storage, id, &Empty {})?;
+    lock_escrow(deps.storage, &order.asset)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "create_conditional_burn")
+        .add_attribute("conditional_burn_id", id.to_string())
+        .add_attribute("owner", order.owner)
+        .add_attribute("asset", order.asset.to_string())
+        .add_attribute("expires_at", order.condition.expires_at.seconds().to_string()))
+}
+
+/// Burns the escrow of an open order whose price condition holds, callable by anyone
+pub fn trigger_conditional_burn(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let mut order = load_open_conditional_burn(deps.as_ref(), id)?;
+    if env.block.time >= order.condition.expires_at {
+        return Err(StdError::generic_err(format!("Conditional burn {} has expired", id)));
+    }
+    let price = query_oracle_price(&deps.querier, &order.condition.source)?;
+    if !order.condition.comparator.holds(price, order.condition.threshold) {
+        return Err(StdError::generic_err(format!(
+            "Condition of conditional burn {} does not hold at price {}",
+            id, price
+        )));
+    }
+
+    order.status = ConditionalBurnStatus::Burned;
+    order.closed_at = Some(env.block.time);
+    CONDITIONAL_BURNS.save(deps.storage, id, &order)?;
+    OPEN_CONDITIONAL_BURNS.remove(deps.storage, id);
+    release_escrow(deps.storage, &order.asset)?;
+
+    let mut messages: Vec<SubMsg<InjectiveMsgWrapper>> = vec![];
+    let mut events = vec![];
+    let receipt = burn_now(deps, &env, &info.sender, &order.owner, order.asset, &mut messages, &mut events)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_events(events)
+        .set_data(to_json_binary(&receipt)?)
+        .add_attribute("action", "trigger_conditional_burn")
+        .add_attribute("conditional_burn_id", id.to_string())
+        .add_attribute("price", price.to_string())
+        .add_attribute("triggered_by", info.sender))
+}
+
+/// Returns the escrow of an expired order to its owner, callable by anyone
+pub fn refund_conditional_burn(deps: DepsMut, env: Env, id: u64) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let mut order = load_open_conditional_burn(deps.as_ref(), id)?;
+    if env.block.time < order.condition.expires_at {
+        return Err(StdError::generic_err(format!(
+            "Conditional burn {} cannot be refunded before {}",
+            id,
+            order.condition.expires_at.seconds()
+        )));
+    }
+
+    order.status = ConditionalBurnStatus::Refunded;
+    order.closed_at = Some(env.block.time);
+    CONDITIONAL_BURNS.save(deps.storage, id, &order)?;
+    OPEN_CONDITIONAL_BURNS.remove(deps.storage, id);
+    release_escrow(deps.storage, &order.asset)?;
+
+    Ok(Response::new()
+        .add_message(refund_escrow(order.asset, order.owner.clone())?)
+        .add_attribute("action", "refund_conditional_burn")
+        .add_attribute("conditional_burn_id", id.to_string())
+        .add_attribute("owner", order.owner))
+}
+
+fn load_open_conditional_burn(deps: Deps, id: u64) -> StdResult<ConditionalBurn> {
+    let order = CONDITIONAL_BURNS.load(deps.storage, id)?;
+    if order.status != ConditionalBurnStatus::Open {
+        return Err(StdError::generic_err(format!("Conditional burn {} is no longer open", id)));
+    }
+    Ok(order)
+}
+
+pub fn query_conditional_burns(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let orders = CONDITIONAL_BURNS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, order)| order))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&orders)
+}
+
+/// Open orders a keeper could trigger at the current block, with the price they would trigger at.
+/// Only the open index is walked and at most `limit` orders are looked at, so the oracle queries per
+/// call stay bounded however many orders fail to match. Orders whose oracle pair has no price are skipped.
+pub fn query_triggerable_conditional_burns(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let ids = OPEN_CONDITIONAL_BURNS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last_scanned = if ids.len() == limit { ids.last().copied() } else { None };
+
+    let mut burns = vec![];
+    for id in ids {
+        let order = CONDITIONAL_BURNS.load(deps.storage, id)?;
+        if env.block.time >= order.condition.expires_at {
+            continue;
+        }
+        let Ok(price) = query_oracle_price(&deps.querier, &order.condition.source) else {
+            continue;
+        };
+        if order.condition.comparator.holds(price, order.condition.threshold) {
+            burns.push(TriggerableConditionalBurn { order, price });
+        }
+    }
+    to_json_binary(&TriggerableConditionalBurnsResponse { burns, last_scanned })
+}
@@ -1,18 +1,80 @@
 use schemars::JsonSchema;
 
-use crate::state::{load_config, save_config, Config, AssetInfo};
-use crate::msg::{InstantiateMsg, ExecuteMsg, QueryMsg};
+use crate::state::{
+    load_config, save_config, BurnDetails, Config, BURN_COUNT, AssetInfo, CONDITIONAL_BURNS, PendingSwap, SwapRoute, CALLBACK_LOCK, RESCUES, SWAP_ROUTES, TRACKED_CW20S,
+};
+use crate::msg::{
+    BurnReceipt, Callback, CallbackExecuteMsg, Cw20HookMsg, Cw20Holding, ExecuteMsg, HoldingsResponse, InstantiateMsg, QueryMsg, RecoveryDestination, SimulateBurnResponse, SubaccountHolding,
+    SwapRouteResponse, submsg_response_data,
+};
+use crate::access::{assert_sender_allowed, query_sender_access, query_sender_list, set_sender_access, update_sender_list};
+use crate::error::ContractError;
+use crate::backend::{load_message_backend, set_message_backend};
+use crate::campaign::{
+    close_campaign, create_campaign, create_native_campaign, match_burn, query_campaign, query_campaign_match,
+    query_campaign_min_pool, query_campaigns, set_campaign_min_pool,
+};
+use crate::certificate::{
+    certificate_mint_message, handle_certificate_reply, query_certificate_config, set_certificate_contract,
+    set_certificate_threshold, CERTIFICATE_REPLY_ID,
+};
+use crate::conditional::{
+    create_conditional_burn, create_native_conditional_burn, query_conditional_burns,
+    query_triggerable_conditional_burns, refund_conditional_burn, trigger_conditional_burn,
+};
+use crate::drive::{
+    attribute_burn, create_drive, query_drive, query_drive_contribution, query_drive_contributors, query_drives,
+    set_drive_creation,
+};
+use crate::idempotency::{guard_request, query_idempotency_key};
+use crate::permit::{burn_with_permit, permit_owner, query_permit_nonce};
+use crate::hooks::{
+    add_hook, burn_hook_messages, handle_hook_reply, query_hooks, remove_hook, set_hook_auto_remove, HOOK_REPLY_ID,
+};
+use crate::points::{
+    create_points_token, handle_points_reply, points_mint_message, query_points_config, set_points_rate,
+    set_points_token, POINTS_REPLY_ID,
+};
+use crate::pledge::{
+    cancel_pledge, create_native_pledge, create_pledge, query_pledge, query_pledges_by_due, query_pledges_by_pledger,
+    trigger_pledge,
+};
+use crate::stream::{cancel_stream, create_native_stream, create_stream, query_stream, query_streams, release_stream};
+use crate::querier::{query_all_balances, query_subaccount_deposit};
+use crate::rate_limit::{
+    check_rate_limit, query_rate_limit, query_rate_limit_exemptions, query_sender_rate_limit, set_rate_limit,
+    set_rate_limit_exemption,
+};
+use crate::referral::{apply_referral, query_referral_cut, query_referral_stats, query_referrer_stats, set_referral_cut};
+use crate::registration::{
+    fund_registration_budget, load_registration_budget, query_registration_allowlist, query_registrations,
+    register_cw20_if_needed, registration_message, set_registration_allowed, withdraw_registration_budget,
+};
+use crate::rescue::{cancel_rescue, execute_rescue, load_rescue_timelock, propose_rescue, query_rescues, set_rescue_timelock};
+use crate::routing::{contract_subaccount_id, load_subaccount_routing, query_subaccount_routing, set_subaccount_routing};
 use cosmwasm_std::{
-    entry_point, to_json_binary, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    Response, StdError, StdResult, WasmMsg, Uint128
+    entry_point, from_json, to_json_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, Event, MessageInfo,
+    Order, Reply, Response, StdError, StdResult, SubMsg, SubMsgResult, WasmMsg, Uint128
 };
 use cw20::{Cw20ReceiveMsg, Cw20ExecuteMsg};
 use injective_cosmwasm::{InjectiveMsgWrapper, InjectiveRoute, InjectiveMsg};
-use injective_cosmwasm::exchange::subaccount::{checked_address_to_subaccount_id};
+use injective_cosmwasm::exchange::order::OrderType;
+use injective_cosmwasm::exchange::spot::SpotOrder;
+use injective_cosmwasm::exchange::subaccount::checked_address_to_subaccount_id;
 use injective_cosmwasm::exchange::types::{SubaccountId};
+use injective_math::{floor, scale::dec_scale_factor, FPDecimal};
+use injective_std::types::injective::exchange::v1beta1::MsgCreateSpotMarketOrderResponse;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 use crate::state::Asset;
+use crate::escrow::assert_unreserved;
+
+pub const SWAP_REPLY_ID: u64 = 1;
+pub const CALLBACK_REPLY_ID: u64 = 3;
+
+/// Gas available to a per-call burn callback
+pub const CALLBACK_GAS_LIMIT: u64 = 500_000;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub enum AdapterExecuteMsg {
@@ -49,91 +111,601 @@ pub fn instantiate(
 
 #[entry_point]
 pub fn execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> StdResult<Response<InjectiveMsgWrapper>> {
-    match msg {
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    if CALLBACK_LOCK.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(StdError::generic_err("Re-entry during a burn callback is not allowed").into());
+    }
+
+    // Everything that brings in funds to burn, CW20s are checked against who sent the tokens
+    let incoming = match &msg {
+        ExecuteMsg::Receive(receive) => {
+            let asset = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender.to_string(),
+                },
+                amount: receive.amount,
+            };
+            Some((Addr::unchecked(&receive.sender), Some(info.sender.clone()), vec![asset]))
+        }
+        ExecuteMsg::SendNative { asset, .. }
+        | ExecuteMsg::CreatePledge { asset, .. }
+        | ExecuteMsg::CreateConditionalBurn { asset, .. }
+        | ExecuteMsg::CreateStream { asset, .. }
+        | ExecuteMsg::CreateCampaign { pool: asset, .. } => Some((info.sender.clone(), None, vec![asset.clone()])),
+        ExecuteMsg::SendBatch { assets } => Some((info.sender.clone(), None, assets.clone())),
+        ExecuteMsg::BurnWithPermit { permit, public_key, .. } => {
+            let asset = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: permit.token.clone(),
+                },
+                amount: permit.amount,
+            };
+            Some((permit_owner(deps.as_ref(), public_key)?, None, vec![asset]))
+        }
+        _ => None,
+    };
+    if let Some((sender, token, assets)) = incoming {
+        assert_sender_allowed(deps.as_ref(), &sender)?;
+        check_rate_limit(deps.branch(), &env, &sender, token.as_ref(), &assets)?;
+    }
+
+    let response = match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
-        ExecuteMsg::SendNative {asset} => send_native(deps, env, info, asset),
+        ExecuteMsg::SendNative {asset, callback, referrer, drive_id, idempotency_key, deadline} => {
+            guard_request(deps.branch(), &env, &info.sender, idempotency_key, deadline)?;
+            send_native(deps, env, info, asset, callback, referrer, drive_id)
+        }
+        ExecuteMsg::SendBatch { assets } => send_batch(deps, env, info, assets),
         ExecuteMsg::UpdateAdmin { admin } => update_admin(deps, info, admin),
-    }
+        ExecuteMsg::CreatePledge { asset, terms } => create_native_pledge(deps, env, info, asset, terms),
+        ExecuteMsg::TriggerPledge { id } => trigger_pledge(deps, env, info, id),
+        ExecuteMsg::CancelPledge { id } => cancel_pledge(deps, env, info, id),
+        ExecuteMsg::CreateConditionalBurn { asset, condition } => {
+            create_native_conditional_burn(deps, env, info, asset, condition)
+        }
+        ExecuteMsg::TriggerConditional { id } => trigger_conditional_burn(deps, env, info, id),
+        ExecuteMsg::RefundConditional { id } => refund_conditional_burn(deps, env, id),
+        ExecuteMsg::CreateStream { asset, schedule } => create_native_stream(deps, env, info, asset, schedule),
+        ExecuteMsg::ReleaseStream { id } => release_stream(deps, env, info, id),
+        ExecuteMsg::CancelStream { id } => cancel_stream(deps, env, info, id),
+        ExecuteMsg::CreateCampaign { pool, terms } => create_native_campaign(deps, env, info, pool, terms),
+        ExecuteMsg::CloseCampaign { id } => close_campaign(deps, env, id),
+        ExecuteMsg::SetCampaignMinPool { asset_info, amount } => {
+            set_campaign_min_pool(deps, info, asset_info, amount)
+        }
+        ExecuteMsg::SetSwapRoute { denom, route } => set_swap_route(deps, info, denom, route),
+        ExecuteMsg::RemoveSwapRoute { denom } => remove_swap_route(deps, info, denom),
+        ExecuteMsg::SetTrackedCw20s { tokens } => set_tracked_cw20s(deps, info, tokens),
+        ExecuteMsg::RecoverSubaccountFunds { nonce, amounts, destination } => {
+            recover_subaccount_funds(deps, env, info, nonce, amounts, destination)
+        }
+        ExecuteMsg::SetRescueTimelock { seconds } => set_rescue_timelock(deps, info, seconds),
+        ExecuteMsg::ProposeRescue { item, recipient } => propose_rescue(deps, env, info, item, recipient),
+        ExecuteMsg::ExecuteRescue { id } => execute_rescue(deps, env, info, id),
+        ExecuteMsg::CancelRescue { id } => cancel_rescue(deps, env, info, id),
+        ExecuteMsg::AddHook { contract } => add_hook(deps, info, contract),
+        ExecuteMsg::RemoveHook { contract } => remove_hook(deps, info, contract),
+        ExecuteMsg::SetHookAutoRemove { enabled } => set_hook_auto_remove(deps, info, enabled),
+        ExecuteMsg::SetCertificateContract { contract } => set_certificate_contract(deps, info, contract),
+        ExecuteMsg::SetCertificateThreshold { asset_info, threshold } => {
+            set_certificate_threshold(deps, info, asset_info, threshold)
+        }
+        ExecuteMsg::SetMessageBackend { backend } => set_message_backend(deps, info, backend),
+        ExecuteMsg::SetSubaccountRouting { routing } => set_subaccount_routing(deps, info, routing),
+        ExecuteMsg::FundRegistrationBudget {} => fund_registration_budget(deps, info),
+        ExecuteMsg::WithdrawRegistrationBudget { amount, recipient } => {
+            withdraw_registration_budget(deps, info, amount, recipient)
+        }
+        ExecuteMsg::SetRegistrationAllowed { cw20, allowed } => set_registration_allowed(deps, info, cw20, allowed),
+        ExecuteMsg::CreatePointsToken { code_id, name, symbol, decimals } => {
+            create_points_token(deps, env, info, code_id, name, symbol, decimals)
+        }
+        ExecuteMsg::SetPointsToken { contract } => set_points_token(deps, info, contract),
+        ExecuteMsg::SetPointsRate { asset_info, rate } => set_points_rate(deps, info, asset_info, rate),
+        ExecuteMsg::SetReferralCut { bps } => set_referral_cut(deps, info, bps),
+        ExecuteMsg::CreateDrive { terms } => create_drive(deps, env, info, terms),
+        ExecuteMsg::SetDriveCreation { permissionless } => set_drive_creation(deps, info, permissionless),
+        ExecuteMsg::SetSenderAccess { mode } => set_sender_access(deps, info, mode),
+        ExecuteMsg::UpdateSenderList { list, add, remove } => update_sender_list(deps, info, list, add, remove),
+        ExecuteMsg::SetRateLimit { limit } => set_rate_limit(deps, info, limit),
+        ExecuteMsg::SetRateLimitExemption { address, exempt } => set_rate_limit_exemption(deps, info, address, exempt),
+        ExecuteMsg::BurnWithPermit { permit, signature, public_key } => {
+            burn_with_permit(deps, env, info, permit, &signature, &public_key)
+        }
+    }?;
+    Ok(response)
 }
 
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetConfig {} => {
             let config = load_config(deps)?;
             to_json_binary(&config)
         }
+        QueryMsg::GetSwapRoute { denom } => {
+            let route = SWAP_ROUTES.load(deps.storage, &denom)?;
+            to_json_binary(&SwapRouteResponse { denom, route })
+        }
+        QueryMsg::GetSwapRoutes {} => {
+            let routes = SWAP_ROUTES
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(denom, route)| SwapRouteResponse { denom, route }))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&routes)
+        }
+        QueryMsg::Holdings { cw20s, denoms } => to_json_binary(&query_holdings(deps, env, cw20s, denoms)?),
+        QueryMsg::SimulateBurn { asset } => to_json_binary(&simulate_burn(deps, env, asset)?),
+        QueryMsg::GetMessageBackend {} => to_json_binary(&load_message_backend(deps)?),
+        QueryMsg::GetSubaccountRouting {} => {
+            to_json_binary(&query_subaccount_routing(deps, &env.contract.address)?)
+        }
+        QueryMsg::GetRegistrationBudget {} => to_json_binary(&load_registration_budget(deps)?),
+        QueryMsg::GetRegistrations { start_after, limit } => query_registrations(deps, start_after, limit),
+        QueryMsg::GetRegistrationAllowlist { start_after, limit } => {
+            query_registration_allowlist(deps, start_after, limit)
+        }
+        QueryMsg::GetRescueTimelock {} => to_json_binary(&load_rescue_timelock(deps)?),
+        QueryMsg::GetRescue { id } => to_json_binary(&RESCUES.load(deps.storage, id)?),
+        QueryMsg::GetRescues { start_after, limit } => query_rescues(deps, start_after, limit),
+        QueryMsg::GetHooks {} => to_json_binary(&query_hooks(deps)?),
+        QueryMsg::GetConditionalBurn { id } => to_json_binary(&CONDITIONAL_BURNS.load(deps.storage, id)?),
+        QueryMsg::GetConditionalBurns { start_after, limit } => query_conditional_burns(deps, start_after, limit),
+        QueryMsg::GetTriggerableConditionalBurns { start_after, limit } => {
+            query_triggerable_conditional_burns(deps, env, start_after, limit)
+        }
+        QueryMsg::GetCampaign { id } => query_campaign(deps, env, id),
+        QueryMsg::GetCampaigns { asset_info, start_after, limit } => {
+            query_campaigns(deps, env, asset_info, start_after, limit)
+        }
+        QueryMsg::GetCampaignMatch { id, user } => query_campaign_match(deps, id, user),
+        QueryMsg::GetCampaignMinPool { asset_info } => query_campaign_min_pool(deps, asset_info),
+        QueryMsg::GetStream { id } => query_stream(deps, env, id),
+        QueryMsg::GetStreams { start_after, limit } => query_streams(deps, env, start_after, limit),
+        QueryMsg::GetPledge { id } => query_pledge(deps, id),
+        QueryMsg::GetPledgesByPledger { pledger, start_after, limit } => {
+            query_pledges_by_pledger(deps, pledger, start_after, limit)
+        }
+        QueryMsg::GetPledgesByDue { unlocked_only, start_after, limit } => {
+            query_pledges_by_due(deps, env, unlocked_only.unwrap_or_default(), start_after, limit)
+        }
+        QueryMsg::GetCertificateConfig {} => to_json_binary(&query_certificate_config(deps)?),
+        QueryMsg::GetPointsConfig {} => to_json_binary(&query_points_config(deps)?),
+        QueryMsg::GetReferralCut {} => to_json_binary(&query_referral_cut(deps)?),
+        QueryMsg::GetReferrerStats { referrer, start_after, limit } => {
+            query_referrer_stats(deps, referrer, start_after, limit)
+        }
+        QueryMsg::GetReferralStats { start_after, limit } => query_referral_stats(deps, start_after, limit),
+        QueryMsg::GetDrive { id } => query_drive(deps, env, id),
+        QueryMsg::GetDrives { start_after, limit } => query_drives(deps, env, start_after, limit),
+        QueryMsg::GetDriveContributors { id, start_after, limit } => {
+            query_drive_contributors(deps, id, start_after, limit)
+        }
+        QueryMsg::GetDriveContribution { id, contributor } => query_drive_contribution(deps, id, contributor),
+        QueryMsg::GetSenderAccess { sender } => to_json_binary(&query_sender_access(deps, sender)?),
+        QueryMsg::GetSenderList { list, start_after, limit } => query_sender_list(deps, list, start_after, limit),
+        QueryMsg::GetRateLimit {} => to_json_binary(&query_rate_limit(deps)?),
+        QueryMsg::GetSenderRateLimit { sender, token } => {
+            to_json_binary(&query_sender_rate_limit(deps, env, sender, token)?)
+        }
+        QueryMsg::GetRateLimitExemptions { start_after, limit } => {
+            query_rate_limit_exemptions(deps, start_after, limit)
+        }
+        QueryMsg::GetIdempotencyKey { sender, key } => to_json_binary(&query_idempotency_key(deps, sender, key)?),
+        QueryMsg::GetPermitNonce { owner } => to_json_binary(&query_permit_nonce(deps, owner)?),
+    }
+}
+
+fn simulate_burn(deps: Deps, env: Env, asset: Asset) -> StdResult<SimulateBurnResponse> {
+    let config = load_config(deps)?;
+    let destination_subaccount = config.burn_auction_subaccount;
+    let converted_denom = match &asset.info {
+        AssetInfo::Token { contract_addr } => {
+            Some(format!("factory/{}/{}", config.adapter_contract, contract_addr))
+        }
+        AssetInfo::NativeToken { .. } => None,
+    };
+
+    let mut messages: Vec<SubMsg<InjectiveMsgWrapper>> = vec![];
+    match simulate_burn_messages(deps, env, asset, &mut messages) {
+        Ok(auction_denom) => Ok(SimulateBurnResponse {
+            allowed: true,
+            error: None,
+            converted_denom,
+            auction_denom: Some(auction_denom),
+            destination_subaccount,
+            messages,
+        }),
+        Err(e) => Ok(SimulateBurnResponse {
+            allowed: false,
+            error: Some(e.to_string()),
+            converted_denom,
+            auction_denom: None,
+            destination_subaccount,
+            messages: vec![],
+        }),
+    }
+}
+
+fn simulate_burn_messages(
+    deps: Deps,
+    env: Env,
+    asset: Asset,
+    messages: &mut Vec<SubMsg<InjectiveMsgWrapper>>,
+) -> StdResult<String> {
+    // Native burns attach exactly the asset, CW20 burns arrive from the token contract
+    let info = match &asset.info {
+        AssetInfo::NativeToken { denom } => MessageInfo {
+            sender: env.contract.address.clone(),
+            funds: vec![Coin {
+                denom: denom.to_string(),
+                amount: asset.amount,
+            }],
+        },
+        AssetInfo::Token { contract_addr } => {
+            let cw20 = deps.api.addr_validate(contract_addr)?;
+            if let Some((register_msg, _)) = registration_message(deps, &cw20)? {
+                messages.push(register_msg);
+            }
+            MessageInfo {
+                sender: cw20,
+                funds: vec![],
+            }
+        }
+    };
+
+    send_to_burn_auction(deps, env, info, asset, messages)
+}
+
+fn query_holdings(
+    deps: Deps,
+    env: Env,
+    cw20s: Option<Vec<String>>,
+    denoms: Option<Vec<String>>,
+) -> StdResult<HoldingsResponse> {
+    let config = load_config(deps)?;
+    let contract_address = env.contract.address;
+
+    let bank = query_all_balances(&deps.querier, contract_address.clone())?;
+
+    let cw20s = match cw20s {
+        Some(cw20s) => cw20s,
+        None => TRACKED_CW20S.may_load(deps.storage)?.unwrap_or_default(),
+    };
+    let cw20 = cw20s
+        .iter()
+        .map(|contract_addr| {
+            let asset_info = AssetInfo::Token {
+                contract_addr: contract_addr.to_string(),
+            };
+            Ok(Cw20Holding {
+                contract_addr: contract_addr.to_string(),
+                balance: asset_info.query_pool(&deps.querier, deps.api, contract_address.clone())?,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // The exchange module can only be asked per denom, so default to every denom we could have deposited
+    let denoms = match denoms {
+        Some(denoms) => denoms,
+        None => {
+            let mut denoms: Vec<String> = bank.iter().map(|coin| coin.denom.clone()).collect();
+            denoms.extend(
+                cw20s
+                    .iter()
+                    .map(|cw20| format!("factory/{}/{}", config.adapter_contract, cw20)),
+            );
+            for item in SWAP_ROUTES.range(deps.storage, None, None, Order::Ascending) {
+                let (denom, route) = item?;
+                denoms.push(denom);
+                denoms.push(route.quote_denom);
+            }
+            denoms.sort();
+            denoms.dedup();
+            denoms
+        }
+    };
+
+    let subaccount_id = contract_subaccount_id(deps, &contract_address)?;
+    let mut subaccount_deposits = vec![];
+    for denom in denoms {
+        let deposit = query_subaccount_deposit(&deps.querier, &subaccount_id, &denom)?;
+        if deposit.total_balance.is_zero() && deposit.available_balance.is_zero() {
+            continue;
+        }
+        subaccount_deposits.push(SubaccountHolding {
+            denom,
+            available_balance: deposit.available_balance,
+            total_balance: deposit.total_balance,
+        });
+    }
+
+    Ok(HoldingsResponse {
+        bank,
+        cw20,
+        subaccount_id: subaccount_id.as_str().to_string(),
+        subaccount_deposits,
+    })
+}
+
+#[entry_point]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response<InjectiveMsgWrapper>> {
+    match msg.id {
+        SWAP_REPLY_ID => handle_swap_reply(deps, env, msg),
+        HOOK_REPLY_ID => handle_hook_reply(deps, msg),
+        CALLBACK_REPLY_ID => handle_callback_reply(deps, msg),
+        POINTS_REPLY_ID => handle_points_reply(deps, msg),
+        CERTIFICATE_REPLY_ID => handle_certificate_reply(msg),
+        id => Err(StdError::generic_err(format!("Unknown reply id: {}", id))),
     }
 }
 
 fn receive_cw20(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: Cw20ReceiveMsg,
 ) -> StdResult<Response<InjectiveMsgWrapper>> {
-    let mut messages: Vec<CosmosMsg<InjectiveMsgWrapper>> = vec![];
+    let mut messages: Vec<SubMsg<InjectiveMsgWrapper>> = vec![];
     let contract_addr = info.sender.clone();
     let burn_amount = msg.amount;
+    // The token contract reports who sent the tokens, it is not re-validated here
+    let sender = Addr::unchecked(&msg.sender);
+    let hook_msg: Cw20HookMsg = if msg.msg.is_empty() {
+        Cw20HookMsg::default()
+    } else {
+        from_json(&msg.msg)?
+    };
+    guard_request(
+        deps.branch(),
+        &env,
+        &sender,
+        hook_msg.idempotency_key.clone(),
+        hook_msg.deadline,
+    )?;
+    let asset = Asset {
+        info: AssetInfo::Token {
+            contract_addr: contract_addr.to_string(),
+        },
+        amount: burn_amount,
+    };
+
+    // Escrowed tokens are burned later, there is nothing to call back, refer or count yet
+    let escrows = [
+        hook_msg.pledge.is_some(),
+        hook_msg.conditional.is_some(),
+        hook_msg.stream.is_some(),
+        hook_msg.campaign.is_some(),
+    ];
+    if escrows.contains(&true) {
+        if hook_msg.callback.is_some() {
+            return Err(StdError::generic_err("Burn callbacks are not supported for escrowed burns"));
+        }
+        if hook_msg.referrer.is_some() {
+            return Err(StdError::generic_err("Referrers are not supported for escrowed burns"));
+        }
+        if hook_msg.drive_id.is_some() {
+            return Err(StdError::generic_err("Drives only count direct burns"));
+        }
+        return match (hook_msg.pledge, hook_msg.conditional, hook_msg.stream, hook_msg.campaign) {
+            (Some(terms), None, None, None) => create_pledge(deps, env, sender, asset, terms),
+            (None, Some(condition), None, None) => create_conditional_burn(deps, env, sender, asset, condition),
+            (None, None, Some(schedule), None) => create_stream(deps, env, sender, asset, schedule),
+            (None, None, None, Some(terms)) => create_campaign(deps, env, sender, asset, terms),
+            _ => Err(StdError::generic_err("Only one of pledge, conditional, stream and campaign can be set")),
+        };
+    }
+
+    register_cw20_if_needed(deps.branch(), &env, &contract_addr, &sender, &mut messages)?;
+
+    // The contract holds all the tokens, the cut is paid out of them and the rest is burned
+    let asset = match &hook_msg.referrer {
+        Some(referrer) => {
+            let (net, payout) = apply_referral(deps.branch(), &env, &sender, referrer, asset)?;
+            messages.extend(payout.map(SubMsg::new));
+            net
+        }
+        None => asset,
+    };
 
     // Call send_to_burn_auction with the CW20 token info
-    send_to_burn_auction(
-        deps,
-        env,
+    let auction_denom = send_to_burn_auction(
+        deps.as_ref(),
+        env.clone(),
         info,
-        Asset {
-            info: AssetInfo::Token {
-                contract_addr: contract_addr.to_string(),
-            },
-            amount: burn_amount,
-        },
+        asset.clone(),
         &mut messages,
     )?;
 
-    Ok(Response::new()
-        .add_messages(messages)
+    let burn = BurnDetails {
+        sender: sender.clone(),
+        beneficiary: sender,
+        asset,
+        auction_denom,
+    };
+    let mut events = vec![];
+    let receipt = after_burn(deps.branch(), &env, &burn, &mut messages, &mut events)?;
+    let goal_event = match hook_msg.drive_id {
+        Some(drive_id) => attribute_burn(deps.branch(), &env, drive_id, &burn)?,
+        None => None,
+    };
+    if let Some(callback) = hook_msg.callback {
+        messages.push(burn_callback_message(deps, &env.contract.address, &burn, callback)?);
+    }
+
+    let mut response = Response::new()
+        .add_submessages(messages)
+        .add_events(events)
+        .add_events(goal_event)
+        .set_data(to_json_binary(&receipt)?)
         .add_attribute("action", "receive_cw20")
         .add_attribute("sender", msg.sender)
-        .add_attribute("amount", burn_amount.to_string()))
+        .add_attribute("amount", burn_amount.to_string());
+    if let Some(referrer) = hook_msg.referrer {
+        response = response.add_attribute("referrer", referrer);
+    }
+    Ok(response)
 }
 
 pub fn send_native(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    asset: Asset
+    asset: Asset,
+    callback: Option<Callback>,
+    referrer: Option<String>,
+    drive_id: Option<u64>,
 ) -> StdResult<Response<InjectiveMsgWrapper>> {
-    let mut messages: Vec<CosmosMsg<InjectiveMsgWrapper>> = vec![];
+    let mut messages: Vec<SubMsg<InjectiveMsgWrapper>> = vec![];
 
     if !asset.info.is_native_token() {
         return Err(StdError::generic_err("Invalid asset: Expected a native token"));
     }
 
-    send_to_burn_auction(
-        deps,
-        env,
+    let sender = info.sender.clone();
+    // The whole asset has to be attached, only what is left after the cut is burned
+    let (info, asset) = match &referrer {
+        Some(referrer) => {
+            asset.assert_sent_native_token_balance(&info)?;
+            let (net, payout) = apply_referral(deps.branch(), &env, &sender, referrer, asset)?;
+            messages.extend(payout.map(SubMsg::new));
+
+            let mut info = info;
+            for coin in info.funds.iter_mut().filter(|coin| coin.denom == net.info.to_string()) {
+                coin.amount = net.amount;
+            }
+            (info, net)
+        }
+        None => (info, asset),
+    };
+    let auction_denom = send_to_burn_auction(
+        deps.as_ref(),
+        env.clone(),
         info,
-        asset,
+        asset.clone(),
         &mut messages,
     )?;
 
-    Ok(Response::new()
-        .add_messages(messages)
-        .add_attribute("action", "send_native"))
+    let burn = BurnDetails {
+        sender: sender.clone(),
+        beneficiary: sender,
+        asset,
+        auction_denom,
+    };
+    let mut events = vec![];
+    let receipt = after_burn(deps.branch(), &env, &burn, &mut messages, &mut events)?;
+    let goal_event = match drive_id {
+        Some(drive_id) => attribute_burn(deps.branch(), &env, drive_id, &burn)?,
+        None => None,
+    };
+    if let Some(callback) = callback {
+        messages.push(burn_callback_message(deps, &env.contract.address, &burn, callback)?);
+    }
+
+    let mut response = Response::new()
+        .add_submessages(messages)
+        .add_events(events)
+        .add_events(goal_event)
+        .set_data(to_json_binary(&receipt)?)
+        .add_attribute("action", "send_native");
+    if let Some(referrer) = referrer {
+        response = response.add_attribute("referrer", referrer);
+    }
+    Ok(response)
 }
 
-fn update_admin(deps: DepsMut, info: MessageInfo, admin: String) -> StdResult<Response<InjectiveMsgWrapper>> {
-    let config = load_config(deps.as_ref())?;
+pub fn send_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: Vec<Asset>,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let mut messages: Vec<SubMsg<InjectiveMsgWrapper>> = vec![];
 
-    // Only the current admin can update
+    if assets.is_empty() {
+        return Err(StdError::generic_err("No assets provided"));
+    }
+    for (i, asset) in assets.iter().enumerate() {
+        if asset.amount.is_zero() {
+            return Err(StdError::generic_err(format!("Zero amount for {}", asset.info)));
+        }
+        if assets[..i].iter().any(|other| other.info.equal(&asset.info)) {
+            return Err(StdError::generic_err(format!("Duplicate asset: {}", asset.info)));
+        }
+    }
+
+    // Every attached coin has to belong to one of the assets, nothing may be left behind
+    for coin in info.funds.iter() {
+        let expected = assets.iter().any(|asset| match &asset.info {
+            AssetInfo::NativeToken { denom } => *denom == coin.denom,
+            AssetInfo::Token { .. } => false,
+        });
+        if !expected {
+            return Err(StdError::generic_err(format!("Unexpected funds provided: {}", coin)));
+        }
+    }
+
+    let mut receipts: Vec<BurnReceipt> = vec![];
+    let mut events = vec![];
+    for asset in assets.iter() {
+        if let AssetInfo::Token { contract_addr } = &asset.info {
+            // Pull the tokens in first, the adapter send below spends them from our balance
+            let transfer_from_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_validate(contract_addr)?.to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: asset.amount,
+                })?,
+                funds: vec![],
+            });
+            messages.push(SubMsg::new(transfer_from_msg));
+
+            let cw20 = deps.api.addr_validate(contract_addr)?;
+            register_cw20_if_needed(deps.branch(), &env, &cw20, &info.sender, &mut messages)?;
+        }
+
+        let auction_denom = send_to_burn_auction(
+            deps.as_ref(),
+            env.clone(),
+            info.clone(),
+            asset.clone(),
+            &mut messages,
+        )?;
+
+        let burn = BurnDetails {
+            sender: info.sender.clone(),
+            beneficiary: info.sender.clone(),
+            asset: asset.clone(),
+            auction_denom,
+        };
+        receipts.push(after_burn(deps.branch(), &env, &burn, &mut messages, &mut events)?);
+    }
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_events(events)
+        .set_data(to_json_binary(&receipts)?)
+        .add_attribute("action", "send_batch")
+        .add_attribute("sender", info.sender)
+        .add_attribute("assets", assets.len().to_string()))
+}
+
+pub fn assert_admin(deps: Deps, info: &MessageInfo) -> StdResult<Config> {
+    let config = load_config(deps)?;
     if info.sender.to_string() != config.admin {
         return Err(StdError::generic_err("Unauthorized"));
     }
+    Ok(config)
+}
+
+fn update_admin(deps: DepsMut, info: MessageInfo, admin: String) -> StdResult<Response<InjectiveMsgWrapper>> {
+    // Only the current admin can update
+    let config = assert_admin(deps.as_ref(), &info)?;
 
     let new_config = Config {
         admin,
@@ -144,22 +716,296 @@ fn update_admin(deps: DepsMut, info: MessageInfo, admin: String) -> StdResult<Re
     Ok(Response::new().add_attribute("action", "update_admin"))
 }
 
-pub fn send_to_burn_auction(
+fn set_swap_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    route: SwapRoute,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    if route.worst_price <= FPDecimal::ZERO || route.min_quantity_tick_size <= FPDecimal::ZERO {
+        return Err(StdError::generic_err("Swap route price and tick size must be positive"));
+    }
+    if route.quote_denom == denom {
+        return Err(StdError::generic_err("Swap route must change the denom"));
+    }
+    SWAP_ROUTES.save(deps.storage, &denom, &route)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_swap_route")
+        .add_attribute("denom", denom)
+        .add_attribute("market_id", route.market_id.as_str()))
+}
+
+fn remove_swap_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    if !SWAP_ROUTES.has(deps.storage, &denom) {
+        return Err(StdError::generic_err(format!("No swap route for {}", denom)));
+    }
+    SWAP_ROUTES.remove(deps.storage, &denom);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_swap_route")
+        .add_attribute("denom", denom))
+}
+
+fn set_tracked_cw20s(
+    deps: DepsMut,
+    info: MessageInfo,
+    tokens: Vec<String>,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let tokens = tokens
+        .iter()
+        .map(|token| deps.api.addr_validate(token).map(|addr| addr.to_string()))
+        .collect::<StdResult<Vec<_>>>()?;
+    TRACKED_CW20S.save(deps.storage, &tokens)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_tracked_cw20s")
+        .add_attribute("count", tokens.len().to_string()))
+}
+
+fn recover_subaccount_funds(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    nonce: u32,
+    amounts: Vec<Coin>,
+    destination: RecoveryDestination,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    if amounts.is_empty() || amounts.iter().any(|coin| coin.amount.is_zero()) {
+        return Err(StdError::generic_err("Recovery amounts must be non-empty and non-zero"));
+    }
+
+    // The default subaccount is the bank balance, which also holds escrows and the registration budget
+    let is_default_subaccount = nonce == 0;
+    if is_default_subaccount {
+        for coin in amounts.iter() {
+            let asset = Asset {
+                info: AssetInfo::NativeToken {
+                    denom: coin.denom.clone(),
+                },
+                amount: coin.amount,
+            };
+            assert_unreserved(deps.as_ref(), &env, &asset)?;
+        }
+    }
+
+    let backend = load_message_backend(deps.as_ref())?;
+    let subaccount_id = checked_address_to_subaccount_id(&env.contract.address, nonce);
+    let mut messages: Vec<CosmosMsg<InjectiveMsgWrapper>> = vec![];
+    let destination_label = match &destination {
+        RecoveryDestination::Address { address } => {
+            let recipient = deps.api.addr_validate(address)?;
+            // Default subaccount funds already are the bank balance
+            if !is_default_subaccount {
+                for coin in amounts.iter() {
+                    messages.push(backend.withdraw(&env.contract.address, &subaccount_id, coin.clone()));
+                }
+            }
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: amounts.clone(),
+            }));
+            recipient.to_string()
+        }
+        RecoveryDestination::BurnAuction {} => {
+            let burn_auction_subaccount = get_burn_auction_subaccount(deps.as_ref())?;
+            for coin in amounts.iter() {
+                messages.push(backend.external_transfer(
+                    &env.contract.address,
+                    &subaccount_id,
+                    &burn_auction_subaccount,
+                    coin.clone(),
+                ));
+            }
+            "burn_auction".to_string()
+        }
+    };
+
+    // One event per coin so every recovered amount can be audited on its own
+    let events = amounts.iter().map(|coin| {
+        Event::new("subaccount_recovery")
+            .add_attribute("admin", info.sender.to_string())
+            .add_attribute("subaccount_id", subaccount_id.as_str())
+            .add_attribute("denom", coin.denom.clone())
+            .add_attribute("amount", coin.amount.to_string())
+            .add_attribute("destination", destination_label.clone())
+    });
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(events)
+        .add_attribute("action", "recover_subaccount_funds")
+        .add_attribute("admin", info.sender.to_string())
+        .add_attribute("destination", destination_label))
+}
+
+/// Everything that reacts to a burn a user sends in directly once its messages are queued, returns
+/// the receipt of the burn. Only these burns are matched by campaigns.
+pub fn after_burn(
+    mut deps: DepsMut,
+    env: &Env,
+    burn: &BurnDetails,
+    messages: &mut Vec<SubMsg<InjectiveMsgWrapper>>,
+    events: &mut Vec<Event>,
+) -> StdResult<BurnReceipt> {
+    let receipt = record_burn(deps.branch(), env, burn, messages, events)?;
+    match_burn(deps, env, burn, messages, events)?;
+    Ok(receipt)
+}
+
+/// Burns an asset this contract already holds, escrowed or pulled in for a permit, for `owner`,
+/// who is recorded as payer and beneficiary. Unknown CW20s are registered with the adapter on
+/// behalf of `triggered_by`. Campaigns do not match these burns, the tokens were not sent in to
+/// be burned right now and a stream would otherwise be matched once per release.
+pub fn burn_now(
+    mut deps: DepsMut,
+    env: &Env,
+    triggered_by: &Addr,
+    owner: &Addr,
+    asset: Asset,
+    messages: &mut Vec<SubMsg<InjectiveMsgWrapper>>,
+    events: &mut Vec<Event>,
+) -> StdResult<BurnReceipt> {
+    let auction_denom = queue_burn(deps.branch(), env, triggered_by, owner, asset.clone(), messages)?;
+
+    let burn = BurnDetails {
+        sender: owner.clone(),
+        beneficiary: owner.clone(),
+        asset,
+        auction_denom,
+    };
+    record_burn(deps, env, &burn, messages, events)
+}
+
+/// Queues the messages burning an asset this contract already holds without reacting to the
+/// burn, returns the denom the auction receives
+pub fn queue_burn(
+    mut deps: DepsMut,
+    env: &Env,
+    triggered_by: &Addr,
+    owner: &Addr,
     asset: Asset,
-    messages: &mut Vec<CosmosMsg<InjectiveMsgWrapper>>,
-) -> StdResult<()> {
-    let burn_auction_subaccount_obj = get_burn_auction_subaccount(deps.as_ref())?;
+    messages: &mut Vec<SubMsg<InjectiveMsgWrapper>>,
+) -> StdResult<String> {
+    // The held funds stand in for the funds a direct burn would attach
+    let held_info = match &asset.info {
+        AssetInfo::NativeToken { denom } => MessageInfo {
+            sender: owner.clone(),
+            funds: vec![Coin::new(asset.amount, denom)],
+        },
+        AssetInfo::Token { contract_addr } => {
+            let cw20 = deps.api.addr_validate(contract_addr)?;
+            register_cw20_if_needed(deps.branch(), env, &cw20, triggered_by, messages)?;
+            MessageInfo {
+                sender: cw20,
+                funds: vec![],
+            }
+        }
+    };
+    send_to_burn_auction(deps.as_ref(), env.clone(), held_info, asset, messages)
+}
 
-    let config = load_config(deps.as_ref())?;
+/// Numbers the burn and queues certificates, points and hooks for it. Matched and escrowed burns
+/// only go through here so campaigns do not match them.
+pub fn record_burn(
+    deps: DepsMut,
+    env: &Env,
+    burn: &BurnDetails,
+    messages: &mut Vec<SubMsg<InjectiveMsgWrapper>>,
+    events: &mut Vec<Event>,
+) -> StdResult<BurnReceipt> {
+    let burn_id = BURN_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    BURN_COUNT.save(deps.storage, &burn_id)?;
+
+    if let Some(mint_msg) = certificate_mint_message(deps.as_ref(), env, burn_id, burn, events)? {
+        messages.push(mint_msg);
+    }
+    if let Some(mint_msg) = points_mint_message(deps.as_ref(), burn)? {
+        messages.push(mint_msg);
+    }
+    messages.extend(burn_hook_messages(deps.as_ref(), burn)?);
+
+    Ok(BurnReceipt {
+        burn_id,
+        payer: burn.sender.clone(),
+        beneficiary: burn.beneficiary.clone(),
+        asset: burn.asset.clone(),
+        auction_denom: burn.auction_denom.clone(),
+        destination_subaccount: load_config(deps.as_ref())?.burn_auction_subaccount,
+        height: env.block.height,
+    })
+}
+
+/// Calls back the contract that asked for a burn. Runs last with a bounded amount of gas, and the
+/// contract stays locked until the reply so the callee cannot re-enter it.
+fn burn_callback_message(
+    deps: DepsMut,
+    contract_address: &Addr,
+    burn: &BurnDetails,
+    callback: Callback,
+) -> StdResult<SubMsg<InjectiveMsgWrapper>> {
+    let callback_contract = deps.api.addr_validate(&callback.contract)?;
+    if callback_contract == *contract_address {
+        return Err(StdError::generic_err("Burn callback cannot target this contract"));
+    }
+
+    CALLBACK_LOCK.save(deps.storage, &true)?;
+
+    let execute_msg = WasmMsg::Execute {
+        contract_addr: callback_contract.to_string(),
+        msg: to_json_binary(&CallbackExecuteMsg::BurnCallback {
+            sender: burn.sender.clone(),
+            beneficiary: burn.beneficiary.clone(),
+            asset: burn.asset.clone(),
+            auction_denom: burn.auction_denom.clone(),
+            msg: callback.msg,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(SubMsg::reply_always(execute_msg, CALLBACK_REPLY_ID).with_gas_limit(CALLBACK_GAS_LIMIT))
+}
+
+fn handle_callback_reply(deps: DepsMut, msg: Reply) -> StdResult<Response<InjectiveMsgWrapper>> {
+    CALLBACK_LOCK.remove(deps.storage);
+
+    // A failed callback reverts the whole burn, the caller asked for both to happen together
+    match msg.result {
+        SubMsgResult::Ok(_) => Ok(Response::new().add_attribute("action", "burn_callback")),
+        SubMsgResult::Err(error) => Err(StdError::generic_err(format!("Burn callback failed: {}", error))),
+    }
+}
+
+/// Queues the messages moving `asset` into the burn auction and returns the denom the auction receives
+pub fn send_to_burn_auction(
+    deps: Deps,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    messages: &mut Vec<SubMsg<InjectiveMsgWrapper>>,
+) -> StdResult<String> {
+    let config = load_config(deps)?;
     let cw20_adapter_address = config.adapter_contract.clone();
 
     let burn_amount = asset.amount;
     let asset_info = asset.info;
+    if burn_amount.is_zero() {
+        return Err(StdError::generic_err("Burn amount must be greater than zero"));
+    }
 
-    if asset_info.is_native_token() {
+    let deposit_denom = if asset_info.is_native_token() {
 
         if info.funds.is_empty() {
             return Err(StdError::generic_err("No funds provided"));
@@ -178,39 +1024,12 @@ pub fn send_to_burn_auction(
             None => {
                 return Err(StdError::generic_err(format!(
                     "Mismatched denomination: expected {}, but no matching funds provided",
-                    asset_info.to_string()
+                    asset_info
                 )));
             }
         }
 
-        // Native token handling
-        let subaccount_id = checked_address_to_subaccount_id(&env.contract.address, 1);
-        let deposit_msg = CosmosMsg::Custom(InjectiveMsgWrapper {
-            route: InjectiveRoute::Exchange,
-            msg_data: InjectiveMsg::Deposit {
-                sender: env.contract.address.clone(),
-                subaccount_id: subaccount_id.clone(),
-                amount: Coin {
-                    denom: asset_info.to_string(),
-                    amount: burn_amount,
-                },
-            },
-        });
-        messages.push(deposit_msg);
-
-        let transfer_msg = CosmosMsg::Custom(InjectiveMsgWrapper {
-            route: InjectiveRoute::Exchange,
-            msg_data: InjectiveMsg::ExternalTransfer {
-                sender: env.contract.address,
-                source_subaccount_id: subaccount_id,
-                destination_subaccount_id: burn_auction_subaccount_obj,
-                amount: Coin {
-                    denom: asset_info.to_string(),
-                    amount: burn_amount,
-                },
-            },
-        });
-        messages.push(transfer_msg);
+        asset_info.to_string()
     } else {
         // CW20 token handling
         let cw20_address = match &asset_info {
@@ -220,13 +1039,6 @@ pub fn send_to_burn_auction(
             }
         };
 
-        let subaccount_id = checked_address_to_subaccount_id(&env.contract.address, 1);
-        let converted_native_denom = format!(
-            "factory/{}/{}",
-            cw20_adapter_address,
-            cw20_address
-        );
-
         let adapter_msg = CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: cw20_address.to_string(), 
             msg: to_json_binary(&Cw20ExecuteMsg::Send {
@@ -236,37 +1048,166 @@ pub fn send_to_burn_auction(
             })?,
             funds: vec![],
         });
-        messages.push(adapter_msg);
-        
-        // After conversion, prepare the deposit message
-        let deposit_msg = CosmosMsg::Custom(InjectiveMsgWrapper {
-            route: InjectiveRoute::Exchange,
-            msg_data: InjectiveMsg::Deposit {
-                sender: env.contract.address.clone(),
-                subaccount_id: subaccount_id.clone(),
-                amount: Coin {
-                    denom: converted_native_denom.clone(),
-                    amount: burn_amount,
-                },
+        messages.push(SubMsg::new(adapter_msg));
+
+        // After conversion the adapter hands back the token factory denom
+        format!(
+            "factory/{}/{}",
+            cw20_adapter_address,
+            cw20_address
+        )
+    };
+
+    let backend = load_message_backend(deps)?;
+    let routing = load_subaccount_routing(deps)?;
+    let subaccount_id = checked_address_to_subaccount_id(&env.contract.address, routing.nonce());
+    // The default subaccount spends the bank balance, so direct routing needs no deposit
+    if !routing.is_direct() {
+        let deposit_msg = backend.deposit(
+            &env.contract.address,
+            &subaccount_id,
+            Coin {
+                denom: deposit_denom.clone(),
+                amount: burn_amount,
             },
-        });
-        messages.push(deposit_msg);
+        );
+        messages.push(SubMsg::new(deposit_msg));
+    }
 
-        // Transfer to the burn auction sub account
-        let transfer_msg = CosmosMsg::Custom(InjectiveMsgWrapper {
+    // Denoms with a swap route are sold first, the reply burns whatever the order filled
+    if let Some(route) = SWAP_ROUTES.may_load(deps.storage, &deposit_denom)? {
+        let quantity = floor(FPDecimal::from(burn_amount), route.min_quantity_tick_size);
+        if quantity.is_zero() {
+            return Err(StdError::generic_err(format!(
+                "Amount {} is below the minimum quantity tick of the swap market",
+                burn_amount
+            )));
+        }
+        // Only whole ticks can be sold, the rest is burned as is rather than left in the subaccount
+        let sold: Uint128 = quantity.int().into();
+        let remainder = burn_amount - sold;
+        if !remainder.is_zero() {
+            let transfer_msg = backend.external_transfer(
+                &env.contract.address,
+                &subaccount_id,
+                &get_burn_auction_subaccount(deps)?,
+                Coin {
+                    denom: deposit_denom.clone(),
+                    amount: remainder,
+                },
+            );
+            messages.push(SubMsg::new(transfer_msg));
+        }
+
+        let order = SpotOrder::new(
+            route.worst_price,
+            quantity,
+            OrderType::SellAtomic,
+            &route.market_id,
+            subaccount_id,
+            Some(env.contract.address.clone()),
+            None,
+        );
+        let order_msg = CosmosMsg::Custom(InjectiveMsgWrapper {
             route: InjectiveRoute::Exchange,
-            msg_data: InjectiveMsg::ExternalTransfer {
+            msg_data: InjectiveMsg::CreateSpotMarketOrder {
                 sender: env.contract.address,
-                source_subaccount_id: subaccount_id,
-                destination_subaccount_id: burn_auction_subaccount_obj,
-                amount: Coin {
-                    denom: converted_native_denom,
-                    amount: burn_amount,
-                },
+                order,
             },
         });
-        messages.push(transfer_msg);
+        let payload = to_json_binary(&PendingSwap {
+            input_denom: deposit_denom,
+            quote_denom: route.quote_denom.clone(),
+            quantity: sold,
+        })?;
+        messages.push(SubMsg::reply_on_success(order_msg, SWAP_REPLY_ID).with_payload(payload));
+
+        return Ok(route.quote_denom);
     }
 
-    Ok(())
-}
\ No newline at end of file
+    // Transfer to the burn auction sub account
+    let transfer_msg = backend.external_transfer(
+        &env.contract.address,
+        &subaccount_id,
+        &get_burn_auction_subaccount(deps)?,
+        Coin {
+            denom: deposit_denom.clone(),
+            amount: burn_amount,
+        },
+    );
+    messages.push(SubMsg::new(transfer_msg));
+
+    Ok(deposit_denom)
+}
+
+fn handle_swap_reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let pending: PendingSwap = from_json(&msg.payload)?;
+    let response = msg.result.into_result().map_err(StdError::generic_err)?;
+    let data = submsg_response_data(&response)
+        .ok_or_else(|| StdError::generic_err("Market order returned no data"))?;
+    let order_response = MsgCreateSpotMarketOrderResponse::try_from(data)?;
+    let results = order_response
+        .results
+        .ok_or_else(|| StdError::generic_err("Market order returned no results"))?;
+
+    // Exchange decimals come back as integers scaled by 10^18
+    let parse = |value: &str| -> StdResult<FPDecimal> {
+        Ok(FPDecimal::from_str(value)? / dec_scale_factor())
+    };
+    let quantity = parse(&results.quantity)?;
+    let price = parse(&results.price)?;
+    let fee = parse(&results.fee)?;
+
+    // The sell proceeds stay in the contract subaccount, net of the taker fee
+    let gross = quantity * price;
+    if gross <= fee {
+        return Err(StdError::generic_err("Market order filled with no proceeds"));
+    }
+    let proceeds: Uint128 = (gross - fee).int().into();
+    if proceeds.is_zero() {
+        return Err(StdError::generic_err("Market order filled with no proceeds"));
+    }
+
+    let backend = load_message_backend(deps.as_ref())?;
+    let subaccount_id = contract_subaccount_id(deps.as_ref(), &env.contract.address)?;
+    let burn_auction_subaccount = get_burn_auction_subaccount(deps.as_ref())?;
+    let mut messages = vec![backend.external_transfer(
+        &env.contract.address,
+        &subaccount_id,
+        &burn_auction_subaccount,
+        Coin {
+            denom: pending.quote_denom.clone(),
+            amount: proceeds,
+        },
+    )];
+
+    // A market order may fill partially, the unsold input is burned as is rather than left behind
+    let mut filled: Uint128 = quantity.int().into();
+    if !quantity.is_int() {
+        filled += Uint128::one();
+    }
+    let unfilled = pending.quantity.saturating_sub(filled);
+    if !unfilled.is_zero() {
+        messages.push(backend.external_transfer(
+            &env.contract.address,
+            &subaccount_id,
+            &burn_auction_subaccount,
+            Coin {
+                denom: pending.input_denom.clone(),
+                amount: unfilled,
+            },
+        ));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "burn_swap_proceeds")
+        .add_attribute("input_denom", pending.input_denom)
+        .add_attribute("order_hash", order_response.order_hash)
+        .add_attribute("filled_quantity", quantity.to_string())
+        .add_attribute("unfilled_quantity", unfilled.to_string())
+        .add_attribute("price", price.to_string())
+        .add_attribute("fee", fee.to_string())
+        .add_attribute("denom", pending.quote_denom)
+        .add_attribute("amount", proceeds.to_string()))
+}
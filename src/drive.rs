@@ -0,0 +1,201 @@
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, Event, MessageInfo, Order, Response, StdError, StdResult,
+};
+use cw_storage_plus::Bound;
+use injective_cosmwasm::InjectiveMsgWrapper;
+
+use crate::contract::assert_admin;
+use crate::msg::DriveResponse;
+use crate::state::{
+    drive_contributions, AssetInfo, BurnDetails, Drive, DriveContribution, DriveTerms, DRIVES, DRIVE_COUNT, DRIVE_CREATION_OPEN,
+};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+const MAX_NAME_LENGTH: usize = 64;
+
+pub fn set_drive_creation(
+    deps: DepsMut,
+    info: MessageInfo,
+    permissionless: bool,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    DRIVE_CREATION_OPEN.save(deps.storage, &permissionless)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_drive_creation")
+        .add_attribute("permissionless", permissionless.to_string()))
+}
+
+/// Starts a drive, only the admin may unless creation was opened to everyone
+pub fn create_drive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    terms: DriveTerms,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    if !DRIVE_CREATION_OPEN.may_load(deps.storage)?.unwrap_or_default() {
+        assert_admin(deps.as_ref(), &info)?;
+    }
+    if terms.name.is_empty() || terms.name.len() > MAX_NAME_LENGTH {
+        return Err(StdError::generic_err(format!(
+            "Drive name must be between 1 and {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    if terms.goal.is_zero() {
+        return Err(StdError::generic_err("Drive goal must be greater than zero"));
+    }
+    if terms.end_time <= terms.start_time {
+        return Err(StdError::generic_err("Drive must end after it starts"));
+    }
+    if terms.end_time <= env.block.time {
+        return Err(StdError::generic_err("Drive must end in the future"));
+    }
+    if let AssetInfo::Token { contract_addr } = &terms.asset_info {
+        deps.api.addr_validate(contract_addr)?;
+    }
+
+    let id = DRIVE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    DRIVE_COUNT.save(deps.storage, &id)?;
+
+    let drive = Drive {
+        id,
+        creator: info.sender,
+        terms,
+        burned: Default::default(),
+        burns: 0,
+        contributors: 0,
+        created_at: env.block.time,
+        goal_reached_at: None,
+    };
+    DRIVES.save(deps.storage, id, &drive)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "create_drive")
+        .add_attribute("drive_id", id.to_string())
+        .add_attribute("creator", drive.creator)
+        .add_attribute("asset_info", drive.terms.asset_info.to_string())
+        .add_attribute("goal", drive.terms.goal.to_string()))
+}
+
+/// Counts a burn towards a running drive of the same asset, credited to the beneficiary.
+/// Returns the event announcing the goal if this burn is the one that reached it.
+pub fn attribute_burn(deps: DepsMut, env: &Env, drive_id: u64, burn: &BurnDetails) -> StdResult<Option<Event>> {
+    let mut drive = DRIVES
+        .may_load(deps.storage, drive_id)?
+        .ok_or_else(|| StdError::generic_err(format!("Drive {} does not exist", drive_id)))?;
+    if !drive.is_running(env.block.time) {
+        return Err(StdError::generic_err(format!("Drive {} is not running", drive_id)));
+    }
+    if !drive.terms.asset_info.equal(&burn.asset.info) {
+        return Err(StdError::generic_err(format!(
+            "Drive {} only counts burns of {}",
+            drive_id, drive.terms.asset_info
+        )));
+    }
+
+    let key = (drive_id, burn.beneficiary.clone());
+    let mut contribution = match drive_contributions().may_load(deps.storage, key.clone())? {
+        Some(contribution) => contribution,
+        None => {
+            drive.contributors += 1;
+            DriveContribution {
+                drive_id,
+                contributor: burn.beneficiary.clone(),
+                burned: Default::default(),
+                burns: 0,
+            }
+        }
+    };
+    contribution.burned += burn.asset.amount;
+    contribution.burns += 1;
+    drive_contributions().save(deps.storage, key, &contribution)?;
+
+    drive.burned += burn.asset.amount;
+    drive.burns += 1;
+    let mut goal_event = None;
+    if drive.goal_reached_at.is_none() && drive.burned >= drive.terms.goal {
+        drive.goal_reached_at = Some(env.block.time);
+        goal_event = Some(
+            Event::new("drive_goal_reached")
+                .add_attribute("drive_id", drive_id.to_string())
+                .add_attribute("goal", drive.terms.goal.to_string())
+                .add_attribute("burned", drive.burned.to_string())
+                .add_attribute("contributors", drive.contributors.to_string())
+                .add_attribute("reached_by", burn.beneficiary.to_string()),
+        );
+    }
+    DRIVES.save(deps.storage, drive_id, &drive)?;
+
+    Ok(goal_event)
+}
+
+fn drive_response(env: &Env, drive: Drive) -> DriveResponse {
+    DriveResponse {
+        remaining: drive.terms.goal.saturating_sub(drive.burned),
+        running: drive.is_running(env.block.time),
+        drive,
+    }
+}
+
+pub fn query_drive(deps: Deps, env: Env, id: u64) -> StdResult<Binary> {
+    to_json_binary(&drive_response(&env, DRIVES.load(deps.storage, id)?))
+}
+
+pub fn query_drives(deps: Deps, env: Env, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let drives = DRIVES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, drive)| drive_response(&env, drive)))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&drives)
+}
+
+/// Leaderboard of a drive, biggest contributors first. `start_after` is the last contributor
+/// of the previous page.
+pub fn query_drive_contributors(
+    deps: Deps,
+    id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    DRIVES.load(deps.storage, id)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let end = match start_after {
+        Some(contributor) => {
+            let contributor = deps.api.addr_validate(&contributor)?;
+            let last = drive_contributions().load(deps.storage, (id, contributor.clone()))?;
+            Some(Bound::exclusive((last.burned.u128(), (id, contributor))))
+        }
+        None => None,
+    };
+
+    let contributors = drive_contributions()
+        .idx
+        .burned
+        .sub_prefix(id)
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| item.map(|(_, contribution)| contribution))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&contributors)
+}
+
+pub fn query_drive_contribution(deps: Deps, id: u64, contributor: String) -> StdResult<Binary> {
+    DRIVES.load(deps.storage, id)?;
+    let contributor = deps.api.addr_validate(&contributor)?;
+    let contribution = drive_contributions()
+        .may_load(deps.storage, (id, contributor.clone()))?
+        .unwrap_or(DriveContribution {
+            drive_id: id,
+            contributor,
+            burned: Default::default(),
+            burns: 0,
+        });
+    to_json_binary(&contribution)
+}
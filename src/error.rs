@@ -11,4 +11,7 @@ pub enum ContractError {
 
     #[error("No native funds sent")]
     NoNativeFunds {},
+
+    #[error("Sender {sender} is rate limited, try again at height {retry_at_height}")]
+    RateLimited { sender: String, retry_at_height: u64 },
 }
@@ -0,0 +1,77 @@
+use cosmwasm_std::{Addr, Coin, CosmosMsg, Deps, Env, MessageInfo, StdError, StdResult, Storage, Uint128};
+use injective_cosmwasm::InjectiveMsgWrapper;
+
+use crate::registration::{load_registration_budget, REGISTRATION_DENOM};
+use crate::state::{Asset, AssetInfo, ESCROWED};
+
+/// Checks that exactly the native `asset` to escrow is attached. CW20s are escrowed by
+/// sending them to this contract, so they cannot come through here.
+pub fn assert_native_escrow(info: &MessageInfo, asset: &Asset) -> StdResult<()> {
+    let denom = match &asset.info {
+        AssetInfo::NativeToken { denom } => denom,
+        AssetInfo::Token { .. } => {
+            return Err(StdError::generic_err(
+                "CW20s are escrowed by sending the tokens to this contract",
+            ))
+        }
+    };
+    if asset.amount.is_zero() {
+        return Err(StdError::generic_err("Escrow amount must be greater than zero"));
+    }
+    if info.funds.len() != 1 || info.funds[0] != Coin::new(asset.amount, denom) {
+        return Err(StdError::generic_err(format!("Escrow requires exactly {} attached", asset)));
+    }
+    Ok(())
+}
+
+/// Adds an asset taken into escrow to the total held of it
+pub fn lock_escrow(storage: &mut dyn Storage, asset: &Asset) -> StdResult<()> {
+    let key = asset.info.to_string();
+    let total = ESCROWED.may_load(storage, &key)?.unwrap_or_default();
+    ESCROWED.save(storage, &key, &(total + asset.amount))
+}
+
+/// Takes an asset leaving escrow, burned or refunded, off the total held of it
+pub fn release_escrow(storage: &mut dyn Storage, asset: &Asset) -> StdResult<()> {
+    let key = asset.info.to_string();
+    let total = ESCROWED.may_load(storage, &key)?.unwrap_or_default().checked_sub(asset.amount)?;
+    if total.is_zero() {
+        ESCROWED.remove(storage, &key);
+        return Ok(());
+    }
+    ESCROWED.save(storage, &key, &total)
+}
+
+/// Part of the contract's balance of an asset that belongs to escrows or the registration budget
+pub fn reserved_amount(deps: Deps, info: &AssetInfo) -> StdResult<Uint128> {
+    let mut reserved = ESCROWED.may_load(deps.storage, &info.to_string())?.unwrap_or_default();
+    if matches!(info, AssetInfo::NativeToken { denom } if denom == REGISTRATION_DENOM) {
+        reserved += load_registration_budget(deps)?;
+    }
+    Ok(reserved)
+}
+
+/// Refuses to move `asset` out of the contract's bank or CW20 balance if that would dip into
+/// funds reserved for escrows or the registration budget
+pub fn assert_unreserved(deps: Deps, env: &Env, asset: &Asset) -> StdResult<()> {
+    let balance = asset.info.query_pool(&deps.querier, deps.api, env.contract.address.clone())?;
+    let available = Asset {
+        info: asset.info.clone(),
+        amount: balance.saturating_sub(reserved_amount(deps, &asset.info)?),
+    };
+    if asset.amount > available.amount {
+        return Err(StdError::generic_err(format!(
+            "Only {} is not reserved for escrows or the registration budget",
+            available
+        )));
+    }
+    Ok(())
+}
+
+/// Sends an escrowed asset back to `recipient`
+pub fn refund_escrow(asset: Asset, recipient: Addr) -> StdResult<CosmosMsg<InjectiveMsgWrapper>> {
+    asset
+        .into_msg(recipient)?
+        .change_custom()
+        .ok_or_else(|| StdError::generic_err("Unexpected custom refund message"))
+}
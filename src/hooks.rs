@@ -0,0 +1,122 @@
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, MessageInfo, Reply, Response, StdError, StdResult, SubMsg,
+    SubMsgResult, WasmMsg,
+};
+use injective_cosmwasm::InjectiveMsgWrapper;
+
+use crate::contract::assert_admin;
+use crate::msg::{HookExecuteMsg, HooksResponse};
+use crate::state::{BurnDetails, HOOKS, HOOK_AUTO_REMOVE};
+
+pub const HOOK_REPLY_ID: u64 = 2;
+
+/// Gas available to each listener. Running out of gas is caught like any other error, without
+/// a limit it would abort the whole burn.
+pub const HOOK_GAS_LIMIT: u64 = 300_000;
+
+/// Every hook is a submessage in each burn, keep the registry small enough to stay cheap
+const MAX_HOOKS: usize = 10;
+
+pub fn add_hook(deps: DepsMut, info: MessageInfo, contract: String) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let contract = deps.api.addr_validate(&contract)?.to_string();
+    let mut hooks = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if hooks.contains(&contract) {
+        return Err(StdError::generic_err(format!("Hook already registered: {}", contract)));
+    }
+    if hooks.len() >= MAX_HOOKS {
+        return Err(StdError::generic_err(format!("Cannot register more than {} hooks", MAX_HOOKS)));
+    }
+    hooks.push(contract.clone());
+    HOOKS.save(deps.storage, &hooks)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_hook")
+        .add_attribute("hook", contract))
+}
+
+pub fn remove_hook(deps: DepsMut, info: MessageInfo, contract: String) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let mut hooks = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if !hooks.contains(&contract) {
+        return Err(StdError::generic_err(format!("Hook not registered: {}", contract)));
+    }
+    hooks.retain(|hook| *hook != contract);
+    HOOKS.save(deps.storage, &hooks)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_hook")
+        .add_attribute("hook", contract))
+}
+
+pub fn set_hook_auto_remove(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    HOOK_AUTO_REMOVE.save(deps.storage, &enabled)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_hook_auto_remove")
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+/// Notifies every registered listener about a burn. Failures are caught in the reply so a broken
+/// listener can never block burns.
+pub fn burn_hook_messages(deps: Deps, burn: &BurnDetails) -> StdResult<Vec<SubMsg<InjectiveMsgWrapper>>> {
+    let hooks = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    let msg = to_json_binary(&HookExecuteMsg::BurnHook {
+        sender: burn.sender.clone(),
+        beneficiary: burn.beneficiary.clone(),
+        asset: burn.asset.clone(),
+        auction_denom: burn.auction_denom.clone(),
+    })?;
+
+    Ok(hooks
+        .into_iter()
+        .map(|hook| {
+            let payload = Binary::from(hook.as_bytes());
+            let execute_msg = WasmMsg::Execute {
+                contract_addr: hook,
+                msg: msg.clone(),
+                funds: vec![],
+            };
+            SubMsg::reply_on_error(execute_msg, HOOK_REPLY_ID)
+                .with_gas_limit(HOOK_GAS_LIMIT)
+                .with_payload(payload)
+        })
+        .collect())
+}
+
+pub fn handle_hook_reply(deps: DepsMut, msg: Reply) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let hook = String::from_utf8(msg.payload.to_vec())?;
+    let error = match msg.result {
+        SubMsgResult::Err(error) => error,
+        SubMsgResult::Ok(_) => return Ok(Response::new()),
+    };
+
+    let mut removed = false;
+    if HOOK_AUTO_REMOVE.may_load(deps.storage)?.unwrap_or_default() {
+        let mut hooks = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+        hooks.retain(|registered| *registered != hook);
+        HOOKS.save(deps.storage, &hooks)?;
+        removed = true;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "burn_hook_failed")
+        .add_attribute("hook", hook)
+        .add_attribute("error", error)
+        .add_attribute("removed", removed.to_string()))
+}
+
+pub fn query_hooks(deps: Deps) -> StdResult<HooksResponse> {
+    Ok(HooksResponse {
+        hooks: HOOKS.may_load(deps.storage)?.unwrap_or_default(),
+        auto_remove: HOOK_AUTO_REMOVE.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
@@ -0,0 +1,56 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, Env, StdError, StdResult};
+
+use crate::msg::IdempotencyKeyResponse;
+use crate::state::{ConsumedKey, BlockPoint, IDEMPOTENCY_KEYS};
+
+const MAX_KEY_LENGTH: usize = 128;
+
+/// Refuses a request past its `deadline`, or whose `idempotency_key` the sender already used.
+/// The key is consumed here, so a retry of a request that went through is rejected.
+pub fn guard_request(
+    deps: DepsMut,
+    env: &Env,
+    sender: &Addr,
+    idempotency_key: Option<String>,
+    deadline: Option<BlockPoint>,
+) -> StdResult<()> {
+    if let Some(deadline) = deadline {
+        if deadline.is_passed(&env.block) {
+            return Err(StdError::generic_err(format!("Request deadline {} has passed", deadline)));
+        }
+    }
+
+    let Some(key) = idempotency_key else {
+        return Ok(());
+    };
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(StdError::generic_err(format!(
+            "Idempotency key must be between 1 and {} characters",
+            MAX_KEY_LENGTH
+        )));
+    }
+    if let Some(consumed) = IDEMPOTENCY_KEYS.may_load(deps.storage, (sender, &key))? {
+        return Err(StdError::generic_err(format!(
+            "Idempotency key {} was already used at height {}",
+            key, consumed.height
+        )));
+    }
+    IDEMPOTENCY_KEYS.save(
+        deps.storage,
+        (sender, &key),
+        &ConsumedKey {
+            height: env.block.height,
+            time: env.block.time,
+        },
+    )
+}
+
+pub fn query_idempotency_key(deps: Deps, sender: String, key: String) -> StdResult<IdempotencyKeyResponse> {
+    let sender = deps.api.addr_validate(&sender)?;
+    let consumed = IDEMPOTENCY_KEYS.may_load(deps.storage, (&sender, &key))?;
+
+    Ok(IdempotencyKeyResponse {
+        used: consumed.is_some(),
+        consumed,
+    })
+}
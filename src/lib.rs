@@ -1,9 +1,28 @@
+pub mod access;
+pub mod backend;
+pub mod campaign;
+pub mod certificate;
+pub mod conditional;
 pub mod contract;
+pub mod drive;
 pub mod error;
+pub mod escrow;
+pub mod hooks;
+pub mod idempotency;
 pub mod msg;
+pub mod permit;
+pub mod pledge;
+pub mod points;
 pub mod state;
 pub mod querier;
+pub mod rate_limit;
+pub mod referral;
+pub mod registration;
+pub mod rescue;
+pub mod routing;
+pub mod stream;
 
+mod multitest;
 mod tests;
 
 pub use crate::contract::{instantiate, execute, query};
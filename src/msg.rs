@@ -1,6 +1,13 @@
+use cosmwasm_std::{from_json, Addr, Binary, Coin, Decimal, StdError, StdResult, SubMsg, SubMsgResponse, Uint128};
 use cw20::Cw20ReceiveMsg;
+use cw_utils::parse_execute_response_data;
+use injective_cosmwasm::InjectiveMsgWrapper;
+use injective_math::FPDecimal;
 use serde::{Deserialize, Serialize};
-use crate::state::{Asset};
+use crate::state::{
+    Asset, AssetInfo, BurnCondition, BurnPermit, BurnStream, Campaign, CampaignTerms, ConditionalBurn, ConsumedKey, Drive, DriveTerms, MessageBackend, BlockPoint, PledgeTerms, RateLimit, SenderAccess, SenderList, RescueItem,
+    StreamSchedule, SubaccountRouting, SwapRoute,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -14,12 +21,528 @@ pub struct InstantiateMsg {
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
-    SendNative {asset: Asset},
+    /// Burns the attached native coins. With a `referrer` the configured referral cut of the
+    /// burn is paid to it and only the rest is burned. With a `drive_id` the burn counts towards
+    /// that drive. The request is refused past its `deadline`, or if the sender already used its
+    /// `idempotency_key`.
+    SendNative {
+        asset: Asset,
+        callback: Option<Callback>,
+        referrer: Option<String>,
+        drive_id: Option<u64>,
+        idempotency_key: Option<String>,
+        deadline: Option<BlockPoint>,
+    },
+    /// Burns several assets at once. Native coins must be attached as funds, CW20s are pulled
+    /// with `TransferFrom` against an allowance granted to this contract.
+    SendBatch { assets: Vec<Asset> },
     UpdateAdmin { admin: String },
+    /// Escrows the attached native coins to be burned once `terms.unlock` is reached.
+    /// CW20 pledges are created with the `pledge` field of the `Receive` payload.
+    CreatePledge { asset: Asset, terms: PledgeTerms },
+    /// Burns an unlocked pledge, anyone may trigger it
+    TriggerPledge { id: u64 },
+    /// Refunds a pledge to its pledger, only before it is locked in
+    CancelPledge { id: u64 },
+    /// Escrows the attached native coins until the oracle price condition holds.
+    /// CW20s use the `conditional` field of the `Receive` payload.
+    CreateConditionalBurn { asset: Asset, condition: BurnCondition },
+    /// Burns an open conditional burn whose condition holds, anyone may trigger it
+    TriggerConditional { id: u64 },
+    /// Returns the escrow of an expired conditional burn to its owner, anyone may trigger it
+    RefundConditional { id: u64 },
+    /// Escrows the attached native coins to be burned linearly over `schedule`.
+    /// CW20s use the `stream` field of the `Receive` payload.
+    CreateStream { asset: Asset, schedule: StreamSchedule },
+    /// Burns the vested part of a stream not released yet, anyone may crank it
+    ReleaseStream { id: u64 },
+    /// Burns what vested and refunds the rest to the owner
+    CancelStream { id: u64 },
+    /// Escrows the attached native coins as a pool matching burns of the same asset.
+    /// CW20 pools use the `campaign` field of the `Receive` payload.
+    CreateCampaign { pool: Asset, terms: CampaignTerms },
+    /// Returns the unused pool of an ended campaign to its sponsor, anyone may trigger it
+    CloseCampaign { id: u64 },
+    /// Smallest pool a campaign on `asset_info` may start with, `None` turns campaigns off for it
+    SetCampaignMinPool { asset_info: AssetInfo, amount: Option<Uint128> },
+    SetSwapRoute { denom: String, route: SwapRoute },
+    RemoveSwapRoute { denom: String },
+    SetTrackedCw20s { tokens: Vec<String> },
+    /// Recovers funds from the subaccount with the given nonce, which need not be the one burns
+    /// are currently routed through. Nonce 0 is the default subaccount, backed by the bank balance.
+    RecoverSubaccountFunds {
+        nonce: u32,
+        amounts: Vec<Coin>,
+        destination: RecoveryDestination,
+    },
+    SetRescueTimelock { seconds: u64 },
+    ProposeRescue { item: RescueItem, recipient: String },
+    ExecuteRescue { id: u64 },
+    CancelRescue { id: u64 },
+    AddHook { contract: String },
+    RemoveHook { contract: String },
+    /// Drop listeners from the registry as soon as one of their hook calls fails
+    SetHookAutoRemove { enabled: bool },
+    /// Collection burn certificates are minted from, `None` stops minting
+    SetCertificateContract { contract: Option<String> },
+    SetCertificateThreshold {
+        asset_info: AssetInfo,
+        threshold: Option<Uint128>,
+    },
+    SetMessageBackend { backend: MessageBackend },
+    SetSubaccountRouting { routing: SubaccountRouting },
+    /// Adds the attached INJ to the budget paying adapter registrations of unknown CW20s
+    FundRegistrationBudget {},
+    /// Sends unspent registration budget to `recipient`, the admin by default
+    WithdrawRegistrationBudget {
+        amount: Uint128,
+        recipient: Option<String>,
+    },
+    /// Lets the registration budget pay for registering `cw20` with the adapter, or stops it
+    SetRegistrationAllowed { cw20: String, allowed: bool },
+    /// Instantiates a burn points cw20 from `code_id` with this contract as minter
+    CreatePointsToken {
+        code_id: u64,
+        name: String,
+        symbol: String,
+        decimals: u8,
+    },
+    /// Points token to mint to burners, `None` stops minting
+    SetPointsToken { contract: Option<String> },
+    /// Points per base unit of the asset, `None` stops minting points for it
+    SetPointsRate {
+        asset_info: AssetInfo,
+        rate: Option<Decimal>,
+    },
+    /// Share of referred burns paid to their referrer, in basis points
+    SetReferralCut { bps: u16 },
+    /// Starts a burn drive, admin only unless drive creation is permissionless
+    CreateDrive { terms: DriveTerms },
+    /// Lets anyone create drives, or only the admin again
+    SetDriveCreation { permissionless: bool },
+    /// Restricts who may burn: everyone, only the allowlist or everyone but the blocklist
+    SetSenderAccess { mode: SenderAccess },
+    UpdateSenderList {
+        list: SenderList,
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Per-sender burn limits over a sliding window of blocks, `None` removes them
+    SetRateLimit { limit: Option<RateLimit> },
+    /// Exempts a high-volume sender from the rate limits, or subjects it again
+    SetRateLimitExemption { address: String, exempt: bool },
+    /// Burns the CW20s of a signed `permit`, submitted by a relayer so the signer needs no gas.
+    /// `signature` is the 64 byte secp256k1 signature over the permit, `public_key` the
+    /// uncompressed 65 byte key of the signer, whose address the tokens are pulled from.
+    BurnWithPermit {
+        permit: BurnPermit,
+        signature: Binary,
+        public_key: Binary,
+    },
+}
+
+/// Payload accepted in the `msg` of a CW20 `Send` to this contract, an empty payload is also accepted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct Cw20HookMsg {
+    pub callback: Option<Callback>,
+    /// Escrows the tokens as a pledge instead of burning them now
+    pub pledge: Option<PledgeTerms>,
+    /// Escrows the tokens until an oracle price condition holds instead of burning them now
+    pub conditional: Option<BurnCondition>,
+    /// Escrows the tokens to be burned over a schedule instead of burning them now
+    pub stream: Option<StreamSchedule>,
+    /// Escrows the tokens as the matching pool of a campaign
+    pub campaign: Option<CampaignTerms>,
+    /// Pays the referral cut of the burn to this address, only for direct burns
+    pub referrer: Option<String>,
+    /// Counts the burn towards this drive, only for direct burns
+    pub drive_id: Option<u64>,
+    /// Rejects the send if the token sender already used this key
+    pub idempotency_key: Option<String>,
+    /// Rejects the send past this height or time
+    pub deadline: Option<BlockPoint>,
+}
+
+/// Contract to call back once the burn messages went through, `msg` is passed back untouched
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Callback {
+    pub contract: String,
+    pub msg: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CallbackExecuteMsg {
+    BurnCallback {
+        sender: Addr,
+        beneficiary: Addr,
+        asset: Asset,
+        auction_denom: String,
+        msg: Binary,
+    },
+}
+
+/// Message every registered hook contract receives after a burn
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookExecuteMsg {
+    BurnHook {
+        sender: Addr,
+        beneficiary: Addr,
+        asset: Asset,
+        auction_denom: String,
+    },
+}
+
+/// Where funds recovered from the contract's exchange subaccount are sent
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryDestination {
+    Address { address: String },
+    BurnAuction {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     GetConfig {},
-}
\ No newline at end of file
+    GetSwapRoute { denom: String },
+    GetSwapRoutes {},
+    /// Balances held by the contract. `cw20s` defaults to the tracked CW20 list and `denoms`
+    /// to every denom the contract knows about when checking its exchange subaccount.
+    Holdings {
+        cw20s: Option<Vec<String>>,
+        denoms: Option<Vec<String>>,
+    },
+    /// Runs the burn validation for `asset` without moving funds. Native assets are
+    /// simulated as if exactly `asset` was attached, CW20s as if sent through `Receive`.
+    SimulateBurn { asset: Asset },
+    GetMessageBackend {},
+    /// Active routing mode and the contract subaccount id it derives
+    GetSubaccountRouting {},
+    GetRegistrationBudget {},
+    /// CW20s registered with the adapter by this contract, ordered by address
+    GetRegistrations {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// CW20s the registration budget may register, ordered by address
+    GetRegistrationAllowlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetRescueTimelock {},
+    GetRescue { id: u64 },
+    GetRescues {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetHooks {},
+    GetConditionalBurn { id: u64 },
+    GetConditionalBurns {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Open conditional burns whose condition holds at the current block, `limit` caps the open
+    /// orders scanned rather than the matches returned
+    GetTriggerableConditionalBurns {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetCampaign { id: u64 },
+    /// Campaigns ordered by id, only the ones matching `asset_info` when it is given
+    GetCampaigns {
+        asset_info: Option<AssetInfo>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// How much of `user`'s burns a campaign matched so far
+    GetCampaignMatch { id: u64, user: String },
+    GetCampaignMinPool { asset_info: AssetInfo },
+    GetStream { id: u64 },
+    GetStreams {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetPledge { id: u64 },
+    /// Pledges of `pledger` in any status, ordered by id
+    GetPledgesByPledger {
+        pledger: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Pending pledges ordered by unlock point, height based ones first. `unlocked_only` keeps
+    /// the ones that can be triggered now. `start_after` is a pledge id.
+    GetPledgesByDue {
+        unlocked_only: Option<bool>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetCertificateConfig {},
+    GetPointsConfig {},
+    GetReferralCut {},
+    /// Referral totals of `referrer` per asset. `start_after` is an asset.
+    GetReferrerStats {
+        referrer: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Referral totals of every referrer, ordered by referrer then asset
+    GetReferralStats {
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+    },
+    GetDrive { id: u64 },
+    GetDrives {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Contributors of a drive, biggest first. `start_after` is the last contributor of the previous page.
+    GetDriveContributors {
+        id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetDriveContribution { id: u64, contributor: String },
+    /// Active access mode, and whether `sender` may burn under it when given
+    GetSenderAccess { sender: Option<String> },
+    GetSenderList {
+        list: SenderList,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetRateLimit {},
+    /// Burns of `sender` counted in the current window, the CW20 burns relayed by `token` when given
+    GetSenderRateLimit { sender: String, token: Option<String> },
+    GetRateLimitExemptions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Whether `sender` already used the idempotency `key`
+    GetIdempotencyKey { sender: String, key: String },
+    /// Nonce the next burn permit of `owner` must carry
+    GetPermitNonce { owner: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PointsConfigResponse {
+    pub token: Option<String>,
+    pub rates: Vec<(String, Decimal)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ReferralCutResponse {
+    pub bps: u16,
+    pub max_bps: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct IdempotencyKeyResponse {
+    pub used: bool,
+    pub consumed: Option<ConsumedKey>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PermitNonceResponse {
+    pub nonce: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct RateLimitResponse {
+    pub limit: Option<RateLimit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SenderRateLimitResponse {
+    pub exempt: bool,
+    pub burns: u32,
+    /// Amounts of the limited assets
+    pub amounts: Vec<Asset>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SenderAccessResponse {
+    pub mode: SenderAccess,
+    pub sender_allowed: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct DriveResponse {
+    pub drive: Drive,
+    /// Left to burn until the goal, zero once reached
+    pub remaining: Uint128,
+    pub running: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct CertificateConfigResponse {
+    pub contract: Option<String>,
+    pub thresholds: Vec<(String, Uint128)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct HooksResponse {
+    pub hooks: Vec<String>,
+    pub auto_remove: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SwapRouteResponse {
+    pub denom: String,
+    pub route: SwapRoute,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct CampaignResponse {
+    pub campaign: Campaign,
+    /// Pool left to match
+    pub remaining: Uint128,
+    /// Whether burns arriving now are matched
+    pub running: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct CampaignMatchResponse {
+    pub matched: Uint128,
+    pub remaining_cap: Uint128,
+    pub match_ratio: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct CampaignMinPoolResponse {
+    /// `None` while campaigns are off for the asset
+    pub min_pool: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct StreamResponse {
+    pub stream: BurnStream,
+    /// Vested at the current block, including what was already released
+    pub vested: Uint128,
+    /// What a `ReleaseStream` would burn now
+    pub releasable: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TriggerableConditionalBurn {
+    pub order: ConditionalBurn,
+    /// Oracle price the order would trigger at
+    pub price: FPDecimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct TriggerableConditionalBurnsResponse {
+    pub burns: Vec<TriggerableConditionalBurn>,
+    /// Last open order looked at, pass it as `start_after` to continue, `None` once all were scanned
+    pub last_scanned: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SubaccountRoutingResponse {
+    pub routing: SubaccountRouting,
+    pub subaccount_id: String,
+}
+
+/// Outcome of a dry-run burn, validation errors are reported in `error` instead of failing the query
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SimulateBurnResponse {
+    pub allowed: bool,
+    pub error: Option<String>,
+    /// Token factory denom a CW20 is converted to by the adapter
+    pub converted_denom: Option<String>,
+    /// Denom that reaches the burn auction
+    pub auction_denom: Option<String>,
+    pub destination_subaccount: String,
+    /// Messages the burn would dispatch, in order. Post-burn hooks, certificates, points and
+    /// campaign matches are not included.
+    pub messages: Vec<SubMsg<InjectiveMsgWrapper>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct HoldingsResponse {
+    pub bank: Vec<Coin>,
+    pub cw20: Vec<Cw20Holding>,
+    pub subaccount_id: String,
+    pub subaccount_deposits: Vec<SubaccountHolding>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Cw20Holding {
+    pub contract_addr: String,
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SubaccountHolding {
+    pub denom: String,
+    pub available_balance: FPDecimal,
+    pub total_balance: FPDecimal,
+}
+
+/// Set as `Response::data` by every burn, `SendBatch` sets a list with one receipt per asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct BurnReceipt {
+    pub burn_id: u64,
+    pub payer: Addr,
+    pub beneficiary: Addr,
+    pub asset: Asset,
+    pub auction_denom: String,
+    pub destination_subaccount: String,
+    pub height: u64,
+}
+
+/// Decodes the receipt of a burn executed as a submessage, for use in the caller's `reply`
+pub fn parse_burn_receipt(response: &SubMsgResponse) -> StdResult<BurnReceipt> {
+    from_json(burn_response_data(response)?)
+}
+
+/// Same as [`parse_burn_receipt`] for the response of a `SendBatch`
+pub fn parse_burn_receipts(response: &SubMsgResponse) -> StdResult<Vec<BurnReceipt>> {
+    from_json(burn_response_data(response)?)
+}
+
+/// Data of the first message of a submessage response. Chains on CosmWasm 2 fill
+/// `msg_responses`, older ones only set the legacy `data` field.
+pub fn submsg_response_data(response: &SubMsgResponse) -> Option<Binary> {
+    #[allow(deprecated)]
+    response
+        .msg_responses
+        .first()
+        .map(|r| r.value.clone())
+        .or_else(|| response.data.clone())
+}
+
+fn burn_response_data(response: &SubMsgResponse) -> StdResult<Binary> {
+    let data =
+        submsg_response_data(response).ok_or_else(|| StdError::generic_err("Burn response has no data"))?;
+
+    parse_execute_response_data(data.as_slice())
+        .map_err(|e| StdError::generic_err(e.to_string()))?
+        .data
+        .ok_or_else(|| StdError::generic_err("Burn response has no receipt"))
+}
@@ -0,0 +1,2177 @@
+#![cfg(test)]
+
+use anyhow::{bail, ensure, Result as AnyResult};
+use cosmwasm_std::testing::{MockApi, MockStorage};
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, AnyMsg, Api, BankMsg, Binary, BlockInfo, CanonicalAddr, Coin, CustomMsg, CustomQuery, Decimal, Deps,
+    DepsMut, Empty, Env, Event, MessageInfo, Order, Querier, QueryRequest, Reply, Response, StdError, StdResult, Storage, Uint128,
+};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+use cw721::msg::{NftInfoResponse, OwnerOfResponse};
+use cw721::DefaultOptionalNftExtension;
+use cw_multi_test::{
+    AddressGenerator, App, AppBuilder, AppResponse, BankKeeper, BankSudo, ContractWrapper, CosmosRouter,
+    DistributionKeeper, Executor, GovFailingModule, IbcFailingModule, Module, StakeKeeper, Stargate, WasmKeeper,
+};
+use cw_storage_plus::Map;
+use injective_cosmwasm::exchange::response::SubaccountDepositResponse;
+use injective_cosmwasm::exchange::subaccount::{checked_address_to_subaccount_id, is_default_subaccount};
+use injective_cosmwasm::exchange::order::OrderType;
+use injective_cosmwasm::exchange::types::{Deposit, MarketId, SubaccountId};
+use injective_cosmwasm::oracle::response::OraclePriceResponse;
+use injective_cosmwasm::oracle::types::{OracleType, PricePairState};
+use injective_cosmwasm::{
+    create_mint_tokens_msg, InjectiveMsg, InjectiveMsgWrapper, InjectiveQuery, InjectiveQueryWrapper, InjectiveRoute,
+};
+use injective_math::FPDecimal;
+use k256::ecdsa::signature::hazmat::PrehashSigner;
+use k256::ecdsa::{Signature, SigningKey};
+use sha3::{Digest, Keccak256};
+use injective_std::types::cosmos::base::v1beta1::Coin as ProtoCoin;
+use injective_std::types::injective::auction::v1beta1::QueryCurrentAuctionBasketResponse;
+use injective_std::types::injective::exchange::v1beta1::{
+    MsgCreateSpotMarketOrderResponse, MsgDeposit, MsgExternalTransfer, MsgWithdraw, SpotMarketOrderResults,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::campaign::MAX_RUNNING_CAMPAIGNS;
+use crate::contract::{execute, instantiate, query, reply};
+use crate::error::ContractError;
+use crate::msg::{
+    BurnReceipt, CampaignMatchResponse, CampaignMinPoolResponse, CampaignResponse, Cw20HookMsg, DriveResponse, ExecuteMsg, IdempotencyKeyResponse, InstantiateMsg, PermitNonceResponse, PointsConfigResponse, QueryMsg,
+    SenderAccessResponse, SimulateBurnResponse, TriggerableConditionalBurnsResponse,
+};
+use crate::permit::permit_digest;
+use crate::registration::AdapterQueryMsg;
+use crate::state::{
+    Asset, AssetInfo, BurnCondition, BurnPermit, CampaignStatus, CampaignTerms, ConditionalBurn, ConditionalBurnStatus, Cw20Registration, DriveContribution, DriveTerms, MessageBackend,
+    OracleSource, Pledge, BlockPoint, PledgeStatus, PledgeTerms, PriceComparator, ReferralStats, SenderAccess, SenderList,
+    StreamSchedule, SubaccountRouting, SwapRoute,
+};
+
+pub const AUCTION_ROUND: u64 = 42;
+pub const BURN_AUCTION_SUBACCOUNT: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+/// Subaccount balances of the mock exchange module, keyed by subaccount id and denom
+const SUBACCOUNT_DEPOSITS: Map<(&str, &str), Uint128> = Map::new("mock_exchange_deposits");
+/// Pair prices of the mock oracle module, keyed by base and quote
+const ORACLE_PRICES: Map<(&str, &str), FPDecimal> = Map::new("mock_oracle_prices");
+
+/// Spot markets of the mock exchange module, keyed by market id
+const SPOT_MARKETS: Map<&str, MockSpotMarket> = Map::new("mock_spot_markets");
+
+/// Resting bids of a mock spot market, sells fill at `price` until `liquidity` of the base runs out
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MockSpotMarket {
+    pub base_denom: String,
+    pub quote_denom: String,
+    pub price: FPDecimal,
+    pub liquidity: Uint128,
+}
+
+/// In-memory stand-in for the Injective exchange and token factory modules. Deposits and
+/// withdrawals move bank funds in and out of the module account, transfers only move
+/// subaccount balances (except out of a default subaccount, which spends bank funds), and mints create `factory/{sender}/...` denoms.
+pub struct InjectiveModule;
+
+impl InjectiveModule {
+    fn module_address(api: &dyn Api) -> AnyResult<Addr> {
+        Ok(api.addr_humanize(&CanonicalAddr::from([0xee; 20].as_slice()))?)
+    }
+
+    fn assert_owner(sender: &Addr, subaccount_id: &SubaccountId) -> AnyResult<()> {
+        // Subaccount ids are the owner's address followed by a 12 byte nonce
+        let owner_prefix = checked_address_to_subaccount_id(sender, 0).as_str()[..42].to_string();
+        ensure!(
+            subaccount_id.as_str().starts_with(&owner_prefix),
+            "{} does not own subaccount {}",
+            sender,
+            subaccount_id.as_str()
+        );
+        Ok(())
+    }
+
+    fn credit(storage: &mut dyn Storage, subaccount_id: &SubaccountId, coin: &Coin) -> AnyResult<()> {
+        let key = (subaccount_id.as_str(), coin.denom.as_str());
+        let balance = SUBACCOUNT_DEPOSITS.may_load(storage, key)?.unwrap_or_default();
+        SUBACCOUNT_DEPOSITS.save(storage, key, &(balance + coin.amount))?;
+        Ok(())
+    }
+
+    fn debit(storage: &mut dyn Storage, subaccount_id: &SubaccountId, coin: &Coin) -> AnyResult<()> {
+        let key = (subaccount_id.as_str(), coin.denom.as_str());
+        let balance = SUBACCOUNT_DEPOSITS.may_load(storage, key)?.unwrap_or_default();
+        ensure!(
+            balance >= coin.amount,
+            "Insufficient subaccount balance: {} has {}{}, needs {}",
+            subaccount_id.as_str(),
+            balance,
+            coin.denom,
+            coin.amount
+        );
+        SUBACCOUNT_DEPOSITS.save(storage, key, &(balance - coin.amount))?;
+        Ok(())
+    }
+
+    /// Applies an exchange or token factory message, whichever encoding it arrived in
+    fn apply<ExecC, QueryC>(
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        msg: InjectiveMsg,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        match msg {
+            InjectiveMsg::Deposit { subaccount_id, amount, .. } => {
+                Self::assert_owner(&sender, &subaccount_id)?;
+                let bank_msg = BankMsg::Send {
+                    to_address: Self::module_address(api)?.to_string(),
+                    amount: vec![amount.clone()],
+                };
+                router.execute(api, storage, block, sender, bank_msg.into())?;
+                Self::credit(storage, &subaccount_id, &amount)?;
+            }
+            InjectiveMsg::Withdraw { subaccount_id, amount, .. } => {
+                Self::assert_owner(&sender, &subaccount_id)?;
+                Self::debit(storage, &subaccount_id, &amount)?;
+                let bank_msg = BankMsg::Send {
+                    to_address: sender.to_string(),
+                    amount: vec![amount],
+                };
+                router.execute(api, storage, block, Self::module_address(api)?, bank_msg.into())?;
+            }
+            InjectiveMsg::SubaccountTransfer {
+                source_subaccount_id,
+                destination_subaccount_id,
+                amount,
+                ..
+            }
+            | InjectiveMsg::ExternalTransfer {
+                source_subaccount_id,
+                destination_subaccount_id,
+                amount,
+                ..
+            } => {
+                Self::assert_owner(&sender, &source_subaccount_id)?;
+                if is_default_subaccount(&source_subaccount_id) {
+                    // The default subaccount is backed by the owner's bank balance
+                    let bank_msg = BankMsg::Send {
+                        to_address: Self::module_address(api)?.to_string(),
+                        amount: vec![amount.clone()],
+                    };
+                    router.execute(api, storage, block, sender, bank_msg.into())?;
+                } else {
+                    Self::debit(storage, &source_subaccount_id, &amount)?;
+                }
+                Self::credit(storage, &destination_subaccount_id, &amount)?;
+            }
+            InjectiveMsg::Mint { amount, mint_to, .. } => {
+                ensure!(
+                    amount.denom.starts_with(&format!("factory/{}/", sender)),
+                    "{} cannot mint {}",
+                    sender,
+                    amount.denom
+                );
+                let mint = BankSudo::Mint {
+                    to_address: mint_to,
+                    amount: vec![amount],
+                };
+                router.sudo(api, storage, block, mint.into())?;
+            }
+            InjectiveMsg::CreateSpotMarketOrder { order, .. } => {
+                let subaccount_id = order.order_info.subaccount_id;
+                Self::assert_owner(&sender, &subaccount_id)?;
+                ensure!(
+                    order.order_type == OrderType::SellAtomic,
+                    "Unsupported order type: {:?}",
+                    order.order_type
+                );
+                let mut market = SPOT_MARKETS.load(storage, order.market_id.as_str())?;
+                ensure!(
+                    market.price >= order.order_info.price,
+                    "Market price {} is below the worst price {}",
+                    market.price,
+                    order.order_info.price
+                );
+                // Fee free, whatever the resting bids cannot take stays unfilled in the subaccount
+                let filled = Uint128::from(order.order_info.quantity.int()).min(market.liquidity);
+                let proceeds = Uint128::from((FPDecimal::from(filled) * market.price).int());
+                Self::debit(storage, &subaccount_id, &Coin::new(filled, &market.base_denom))?;
+                Self::credit(storage, &subaccount_id, &Coin::new(proceeds, &market.quote_denom))?;
+                market.liquidity -= filled;
+                SPOT_MARKETS.save(storage, order.market_id.as_str(), &market)?;
+
+                let response = MsgCreateSpotMarketOrderResponse {
+                    order_hash: "0x01".to_string(),
+                    results: Some(SpotMarketOrderResults {
+                        quantity: FPDecimal::from(filled).num.to_string(),
+                        price: market.price.num.to_string(),
+                        fee: "0".to_string(),
+                    }),
+                    cid: "".to_string(),
+                };
+                return Ok(AppResponse {
+                    data: Some(Binary::from(response)),
+                    ..AppResponse::default()
+                });
+            }
+            msg => bail!("Unsupported Injective message: {:?}", msg),
+        }
+
+        Ok(AppResponse::default())
+    }
+}
+
+impl Module for InjectiveModule {
+    type ExecT = InjectiveMsgWrapper;
+    type QueryT = InjectiveQueryWrapper;
+    type SudoT = Empty;
+
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        msg: Self::ExecT,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        Self::apply(api, storage, router, block, sender, msg.msg_data)
+    }
+
+    fn query(
+        &self,
+        _api: &dyn Api,
+        storage: &dyn Storage,
+        _querier: &dyn Querier,
+        _block: &BlockInfo,
+        request: Self::QueryT,
+    ) -> AnyResult<Binary> {
+        match request.query_data {
+            InjectiveQuery::SubaccountDeposit { subaccount_id, denom } => {
+                let balance = SUBACCOUNT_DEPOSITS
+                    .may_load(storage, (subaccount_id.as_str(), denom.as_str()))?
+                    .unwrap_or_default();
+                let balance = FPDecimal::from(balance);
+                Ok(to_json_binary(&SubaccountDepositResponse {
+                    deposits: Deposit {
+                        available_balance: balance,
+                        total_balance: balance,
+                    },
+                })?)
+            }
+            InjectiveQuery::OraclePrice { base, quote, .. } => {
+                let price_pair_state = ORACLE_PRICES
+                    .may_load(storage, (base.as_str(), quote.as_str()))?
+                    .map(|pair_price| PricePairState {
+                        pair_price,
+                        base_price: pair_price,
+                        quote_price: FPDecimal::ONE,
+                        base_cumulative_price: FPDecimal::ZERO,
+                        quote_cumulative_price: FPDecimal::ZERO,
+                        base_timestamp: 0,
+                        quote_timestamp: 0,
+                    });
+                Ok(to_json_binary(&OraclePriceResponse { price_pair_state })?)
+            }
+            query => bail!("Unsupported Injective query: {:?}", query),
+        }
+    }
+
+    fn sudo<ExecC, QueryC>(
+        &self,
+        _api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        _block: &BlockInfo,
+        msg: Self::SudoT,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        bail!("Unexpected custom sudo: {:?}", msg)
+    }
+}
+
+fn from_proto_coin(coin: Option<ProtoCoin>) -> AnyResult<Coin> {
+    let coin = coin.ok_or_else(|| anyhow::anyhow!("Missing amount"))?;
+    Ok(Coin::new(coin.amount.parse::<Uint128>()?, coin.denom))
+}
+
+/// Answers the auction module queries the contract relies on and applies protobuf encoded
+/// exchange messages through the same in-memory exchange as the custom bindings
+pub struct InjectiveStargate;
+
+impl Stargate for InjectiveStargate {
+    fn execute_any<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        msg: AnyMsg,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        let msg = match msg.type_url.as_str() {
+            MsgDeposit::TYPE_URL => {
+                let msg = MsgDeposit::try_from(msg.value)?;
+                InjectiveMsg::Deposit {
+                    sender: Addr::unchecked(msg.sender),
+                    subaccount_id: SubaccountId::unchecked(msg.subaccount_id),
+                    amount: from_proto_coin(msg.amount)?,
+                }
+            }
+            MsgWithdraw::TYPE_URL => {
+                let msg = MsgWithdraw::try_from(msg.value)?;
+                InjectiveMsg::Withdraw {
+                    sender: Addr::unchecked(msg.sender),
+                    subaccount_id: SubaccountId::unchecked(msg.subaccount_id),
+                    amount: from_proto_coin(msg.amount)?,
+                }
+            }
+            MsgExternalTransfer::TYPE_URL => {
+                let msg = MsgExternalTransfer::try_from(msg.value)?;
+                InjectiveMsg::ExternalTransfer {
+                    sender: Addr::unchecked(msg.sender),
+                    source_subaccount_id: SubaccountId::unchecked(msg.source_subaccount_id),
+                    destination_subaccount_id: SubaccountId::unchecked(msg.destination_subaccount_id),
+                    amount: from_proto_coin(msg.amount)?,
+                }
+            }
+            type_url => bail!("Unsupported Any message: {}", type_url),
+        };
+
+        InjectiveModule::apply(api, storage, router, block, sender, msg)
+    }
+
+    fn query_stargate(
+        &self,
+        _api: &dyn Api,
+        _storage: &dyn Storage,
+        _querier: &dyn Querier,
+        _block: &BlockInfo,
+        path: String,
+        data: Binary,
+    ) -> AnyResult<Binary> {
+        match path.as_str() {
+            "/injective.auction.v1beta1.Query/CurrentAuctionBasket" => {
+                Ok(to_json_binary(&QueryCurrentAuctionBasketResponse {
+                    amount: vec![],
+                    auctionRound: AUCTION_ROUND,
+                    auctionClosingTime: 0,
+                    highestBidder: String::new(),
+                    highestBidAmount: String::new(),
+                })?)
+            }
+            _ => bail!("Unexpected stargate query: path={}, data={}", path, data),
+        }
+    }
+}
+
+/// Contract addresses have to be 20 bytes long to derive Injective subaccount ids from them
+pub struct InjectiveAddressGenerator;
+
+impl AddressGenerator for InjectiveAddressGenerator {
+    fn contract_address(
+        &self,
+        api: &dyn Api,
+        _storage: &mut dyn Storage,
+        code_id: u64,
+        instance_id: u64,
+    ) -> AnyResult<Addr> {
+        let mut bytes = [0u8; 20];
+        bytes[..8].copy_from_slice(&code_id.to_be_bytes());
+        bytes[12..].copy_from_slice(&instance_id.to_be_bytes());
+
+        Ok(api.addr_humanize(&CanonicalAddr::from(bytes.as_slice()))?)
+    }
+}
+
+pub type InjectiveApp = App<
+    BankKeeper,
+    MockApi,
+    MockStorage,
+    InjectiveModule,
+    WasmKeeper<InjectiveMsgWrapper, InjectiveQueryWrapper>,
+    StakeKeeper,
+    DistributionKeeper,
+    IbcFailingModule,
+    GovFailingModule,
+    InjectiveStargate,
+>;
+
+pub fn mock_app(balances: &[(&str, Vec<Coin>)]) -> InjectiveApp {
+    let api = MockApi::default().with_prefix("inj");
+    let balances = balances
+        .iter()
+        .map(|(name, coins)| (api.addr_make(name), coins.clone()))
+        .collect::<Vec<_>>();
+
+    AppBuilder::new_custom()
+        .with_api(api)
+        .with_custom(InjectiveModule)
+        .with_stargate(InjectiveStargate)
+        .with_wasm(WasmKeeper::new().with_address_generator(InjectiveAddressGenerator))
+        .build(|router, _api, storage| {
+            for (addr, coins) in balances {
+                router.bank.init_balance(storage, &addr, coins).unwrap();
+            }
+        })
+}
+
+/// Sets the price the mock oracle reports for `base`/`quote`, whatever the oracle type
+pub fn set_oracle_price(app: &mut InjectiveApp, base: &str, quote: &str, price: FPDecimal) {
+    app.init_modules(|_, _, storage| ORACLE_PRICES.save(storage, (base, quote), &price))
+        .unwrap();
+}
+
+/// Allowlists a CW20 for registration with the adapter paid from the registration budget
+pub fn allow_registration(app: &mut InjectiveApp, admin: &Addr, burn_contract: &Addr, cw20: &Addr) {
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::SetRegistrationAllowed { cw20: cw20.to_string(), allowed: true },
+        &[],
+    )
+    .unwrap();
+}
+
+pub fn set_spot_market(app: &mut InjectiveApp, market_id: &str, market: MockSpotMarket) {
+    app.init_modules(|_, _, storage| SPOT_MARKETS.save(storage, market_id, &market))
+        .unwrap();
+}
+
+pub fn query_subaccount_balance(app: &InjectiveApp, subaccount_id: &str, denom: &str) -> FPDecimal {
+    let request = QueryRequest::Custom(InjectiveQueryWrapper {
+        route: InjectiveRoute::Exchange,
+        query_data: InjectiveQuery::SubaccountDeposit {
+            subaccount_id: SubaccountId::unchecked(subaccount_id),
+            denom: denom.to_string(),
+        },
+    });
+    let res: SubaccountDepositResponse = app.wrap().query(&request).unwrap();
+
+    res.deposits.total_balance
+}
+
+fn burn_instantiate(
+    deps: DepsMut<InjectiveQueryWrapper>,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let res = instantiate(deps.into_empty(), env, info, msg)?;
+
+    Ok(res.change_custom().unwrap())
+}
+
+fn burn_execute(
+    deps: DepsMut<InjectiveQueryWrapper>,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
+    execute(deps.into_empty(), env, info, msg)
+}
+
+fn burn_query(deps: Deps<InjectiveQueryWrapper>, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    query(deps.into_empty(), env, msg)
+}
+
+fn burn_reply(deps: DepsMut<InjectiveQueryWrapper>, env: Env, msg: Reply) -> StdResult<Response<InjectiveMsgWrapper>> {
+    reply(deps.into_empty(), env, msg)
+}
+
+pub fn store_and_instantiate_burn_contract(app: &mut InjectiveApp, admin: &Addr, adapter: &Addr) -> Addr {
+    let code_id = app.store_code(Box::new(
+        ContractWrapper::new(burn_execute, burn_instantiate, burn_query).with_reply(burn_reply),
+    ));
+
+    app.instantiate_contract(
+        code_id,
+        admin.clone(),
+        &InstantiateMsg {
+            admin: admin.to_string(),
+            adapter_contract: adapter.to_string(),
+            burn_auction_subaccount: BURN_AUCTION_SUBACCOUNT.to_string(),
+        },
+        &[],
+        "burn",
+        None,
+    )
+    .unwrap()
+}
+
+/// Fee the mock adapter charges to register a CW20
+pub const ADAPTER_REGISTRATION_FEE: u128 = 100;
+
+/// CW20s registered with the mock adapter
+const ADAPTER_REGISTERED: Map<&Addr, bool> = Map::new("registered");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MockAdapterExecuteMsg {
+    RegisterCw20Contract { addr: Addr },
+    Receive(Cw20ReceiveMsg),
+}
+
+/// Mimics the CW20 adapter: registered CW20s sent to it are minted back to the sender as `factory/{adapter}/{cw20}`
+fn adapter_execute(
+    deps: DepsMut<InjectiveQueryWrapper>,
+    env: Env,
+    info: MessageInfo,
+    msg: MockAdapterExecuteMsg,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    match msg {
+        MockAdapterExecuteMsg::RegisterCw20Contract { addr } => {
+            if info.funds != vec![Coin::new(ADAPTER_REGISTRATION_FEE, "inj")] {
+                return Err(StdError::generic_err("Registration fee not paid"));
+            }
+            ADAPTER_REGISTERED.save(deps.storage, &addr, &true)?;
+            Ok(Response::new())
+        }
+        MockAdapterExecuteMsg::Receive(msg) => {
+            if !ADAPTER_REGISTERED.has(deps.storage, &info.sender) {
+                return Err(StdError::generic_err(format!("Contract {} is not registered", info.sender)));
+            }
+            let denom = format!("factory/{}/{}", env.contract.address, info.sender);
+
+            Ok(Response::new().add_message(create_mint_tokens_msg(
+                env.contract.address,
+                Coin::new(msg.amount, denom),
+                msg.sender,
+            )))
+        }
+    }
+}
+
+fn adapter_instantiate(
+    _deps: DepsMut<InjectiveQueryWrapper>,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    Ok(Response::new())
+}
+
+fn adapter_query(deps: Deps<InjectiveQueryWrapper>, _env: Env, msg: AdapterQueryMsg) -> StdResult<Binary> {
+    match msg {
+        AdapterQueryMsg::RegisteredContracts {} => {
+            let registered = ADAPTER_REGISTERED
+                .keys(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&registered)
+        }
+        AdapterQueryMsg::NewDenomFee {} => to_json_binary(&Uint128::new(ADAPTER_REGISTRATION_FEE)),
+    }
+}
+
+pub fn store_and_instantiate_adapter(app: &mut InjectiveApp, admin: &Addr) -> Addr {
+    let code_id = app.store_code(Box::new(ContractWrapper::new(
+        adapter_execute,
+        adapter_instantiate,
+        adapter_query,
+    )));
+
+    app.instantiate_contract(code_id, admin.clone(), &Empty {}, &[], "cw20 adapter", None)
+        .unwrap()
+}
+
+pub fn store_and_instantiate_cw20(app: &mut InjectiveApp, admin: &Addr, initial_balances: Vec<Cw20Coin>) -> Addr {
+    let code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    )));
+
+    app.instantiate_contract(
+        code_id,
+        admin.clone(),
+        &cw20_base::msg::InstantiateMsg {
+            name: "Test token".to_string(),
+            symbol: "TEST".to_string(),
+            decimals: 6,
+            initial_balances,
+            mint: None,
+            marketing: None,
+        },
+        &[],
+        "cw20",
+        None,
+    )
+    .unwrap()
+}
+
+/// Signs a burn permit the way a wallet would, returns the signature and uncompressed public key
+pub fn sign_permit(key: &SigningKey, permit: &BurnPermit) -> (Binary, Binary) {
+    let signature: Signature = key.sign_prehash(&permit_digest(permit).unwrap()).unwrap();
+    let public_key = key.verifying_key().to_encoded_point(false);
+    (
+        Binary::from(signature.to_bytes().as_slice()),
+        Binary::from(public_key.as_bytes()),
+    )
+}
+
+/// Injective address of a signing key, derived from its Ethereum style address
+pub fn permit_signer(api: &MockApi, key: &SigningKey) -> Addr {
+    let public_key = key.verifying_key().to_encoded_point(false);
+    let hash = Keccak256::digest(&public_key.as_bytes()[1..]);
+    api.addr_humanize(&CanonicalAddr::from(&hash[12..])).unwrap()
+}
+
+/// App with the adapter and the burn contract deployed, returns (app, admin, adapter, burn contract)
+pub fn setup(balances: &[(&str, Vec<Coin>)]) -> (InjectiveApp, Addr, Addr, Addr) {
+    let mut app = mock_app(balances);
+    let admin = app.api().addr_make("admin");
+    let adapter = store_and_instantiate_adapter(&mut app, &admin);
+    let burn_contract = store_and_instantiate_burn_contract(&mut app, &admin, &adapter);
+
+    (app, admin, adapter, burn_contract)
+}
+
+type CertificateExecuteMsg = burn_certificates::ExecuteMsg;
+type CertificateQueryMsg = burn_certificates::QueryMsg;
+
+pub fn store_and_instantiate_certificates(app: &mut InjectiveApp, admin: &Addr, minter: &Addr) -> Addr {
+    let code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+        burn_certificates::execute,
+        burn_certificates::instantiate,
+        burn_certificates::query,
+    )));
+
+    app.instantiate_contract(
+        code_id,
+        admin.clone(),
+        &burn_certificates::InstantiateMsg {
+            name: "Burn certificates".to_string(),
+            symbol: "BURN".to_string(),
+            collection_info_extension: None,
+            minter: Some(minter.to_string()),
+            creator: None,
+            withdraw_address: None,
+        },
+        &[],
+        "certificates",
+        None,
+    )
+    .unwrap()
+}
+
+pub fn store_points_code(app: &mut InjectiveApp) -> u64 {
+    app.store_code(Box::new(ContractWrapper::new_with_empty(
+        burn_points::execute,
+        burn_points::instantiate,
+        burn_points::query,
+    )))
+}
+
+#[test]
+fn test_certificate_minted_above_threshold() {
+    let (mut app, admin, _, burn_contract) = setup(&[("burner", vec![Coin::new(1_000u128, "inj")])]);
+    let burner = app.api().addr_make("burner");
+    let certificates = store_and_instantiate_certificates(&mut app, &admin, &burn_contract);
+
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::SetCertificateContract { contract: Some(certificates.to_string()) },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::SetCertificateThreshold {
+            asset_info: AssetInfo::NativeToken { denom: "inj".to_string() },
+            threshold: Some(Uint128::new(100)),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let burn = |app: &mut InjectiveApp, amount: u128| {
+        app.execute_contract(
+            burner.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::SendNative {
+                asset: Asset {
+                    info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                    amount: Uint128::new(amount),
+                },
+                callback: None,
+                referrer: None,
+                drive_id: None,
+                idempotency_key: None,
+                deadline: None,
+            },
+            &[Coin::new(amount, "inj")],
+        )
+        .unwrap()
+    };
+
+    // a burn at the threshold earns nothing
+    burn(&mut app, 100);
+    let res: StdResult<OwnerOfResponse> = app.wrap().query_wasm_smart(
+        &certificates,
+        &CertificateQueryMsg::OwnerOf { token_id: "1".to_string(), include_expired: None },
+    );
+    assert!(res.is_err());
+
+    burn(&mut app, 101);
+    let owner: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(&certificates, &CertificateQueryMsg::OwnerOf { token_id: "2".to_string(), include_expired: None })
+        .unwrap();
+    assert_eq!(owner.owner, burner.to_string());
+
+    let nft: NftInfoResponse<DefaultOptionalNftExtension> = app
+        .wrap()
+        .query_wasm_smart(&certificates, &CertificateQueryMsg::NftInfo { token_id: "2".to_string() })
+        .unwrap();
+    let attributes = nft
+        .extension
+        .unwrap()
+        .attributes
+        .unwrap()
+        .into_iter()
+        .map(|attribute| (attribute.trait_type, attribute.value))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        attributes,
+        vec![
+            ("asset".to_string(), "inj".to_string()),
+            ("amount".to_string(), "101".to_string()),
+            ("round".to_string(), AUCTION_ROUND.to_string()),
+            ("timestamp".to_string(), app.block_info().time.seconds().to_string()),
+        ]
+    );
+
+    // certificates stay with the burner
+    let err = app
+        .execute_contract(
+            burner.clone(),
+            certificates.clone(),
+            &CertificateExecuteMsg::TransferNft {
+                recipient: admin.to_string(),
+                token_id: "2".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err.root_cause().to_string().contains("Certificates are soulbound"));
+
+    // a collection this contract cannot mint in costs the certificate, not the burn
+    let foreign = store_and_instantiate_certificates(&mut app, &admin, &admin);
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::SetCertificateContract { contract: Some(foreign.to_string()) },
+        &[],
+    )
+    .unwrap();
+    let res = burn(&mut app, 101);
+    assert!(res.has_event(
+        &Event::new("wasm-certificate_mint_failed").add_attribute("burn_id", "3")
+    ));
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(302u128));
+}
+
+#[test]
+fn test_points_minted_per_burn() {
+    let (mut app, admin, _, burn_contract) = setup(&[("burner", vec![Coin::new(1_000u128, "inj")])]);
+    let burner = app.api().addr_make("burner");
+    let points_code_id = store_points_code(&mut app);
+
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::CreatePointsToken {
+            code_id: points_code_id,
+            name: "Burn points".to_string(),
+            symbol: "BPTS".to_string(),
+            decimals: 18,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::SetPointsRate {
+            asset_info: AssetInfo::NativeToken { denom: "inj".to_string() },
+            rate: Some(Decimal::percent(250)),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let config: PointsConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&burn_contract, &QueryMsg::GetPointsConfig {})
+        .unwrap();
+    let points_token = config.token.unwrap();
+    assert_eq!(config.rates, vec![("inj".to_string(), Decimal::percent(250))]);
+
+    app.execute_contract(
+        burner.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::SendNative {
+            asset: Asset {
+                info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                amount: Uint128::new(101),
+            },
+            callback: None,
+            referrer: None,
+            drive_id: None,
+            idempotency_key: None,
+            deadline: None,
+        },
+        &[Coin::new(101u128, "inj")],
+    )
+    .unwrap();
+
+    let balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(&points_token, &Cw20QueryMsg::Balance { address: burner.to_string() })
+        .unwrap();
+    assert_eq!(balance.balance, Uint128::new(252));
+
+    let err = app
+        .execute_contract(
+            burner.clone(),
+            Addr::unchecked(&points_token),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: admin.to_string(),
+                amount: Uint128::new(1),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err.root_cause().to_string().contains("Points are non-transferable"));
+
+    // only the burn contract can mint
+    app.execute_contract(
+        admin.clone(),
+        Addr::unchecked(&points_token),
+        &Cw20ExecuteMsg::Mint {
+            recipient: admin.to_string(),
+            amount: Uint128::new(1),
+        },
+        &[],
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn test_native_burn_reaches_burn_auction_subaccount() {
+    let (mut app, _, _, burn_contract) = setup(&[("burner", vec![Coin::new(1_000u128, "inj")])]);
+    let burner = app.api().addr_make("burner");
+
+    app.execute_contract(
+        burner.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::SendNative {
+            asset: Asset {
+                info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                amount: Uint128::new(400),
+            },
+            callback: None,
+            referrer: None,
+            drive_id: None,
+            idempotency_key: None,
+            deadline: None,
+        },
+        &[Coin::new(400u128, "inj")],
+    )
+    .unwrap();
+
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(400u128));
+    let contract_subaccount = checked_address_to_subaccount_id(&burn_contract, 1);
+    assert_eq!(query_subaccount_balance(&app, contract_subaccount.as_str(), "inj"), FPDecimal::ZERO);
+    assert_eq!(app.wrap().query_balance(&burner, "inj").unwrap().amount, Uint128::new(600));
+    assert_eq!(app.wrap().query_balance(&burn_contract, "inj").unwrap().amount, Uint128::zero());
+}
+
+#[test]
+fn test_partially_filled_swap_burns_unsold_input() {
+    let (mut app, admin, _, burn_contract) = setup(&[("burner", vec![Coin::new(20_000u128, "lp")])]);
+    let burner = app.api().addr_make("burner");
+    let market_id = "0x0611780ba69656949525013d947713300f56c37b6175e02f26bffa495c3208fe";
+    // The bids only take 6000 of the 10000 sold
+    set_spot_market(
+        &mut app,
+        market_id,
+        MockSpotMarket {
+            base_denom: "lp".to_string(),
+            quote_denom: "inj".to_string(),
+            price: FPDecimal::must_from_str("0.5"),
+            liquidity: Uint128::new(6_000),
+        },
+    );
+    app.execute_contract(
+        admin,
+        burn_contract.clone(),
+        &ExecuteMsg::SetSwapRoute {
+            denom: "lp".to_string(),
+            route: SwapRoute {
+                market_id: MarketId::unchecked(market_id),
+                quote_denom: "inj".to_string(),
+                worst_price: FPDecimal::must_from_str("0.5"),
+                min_quantity_tick_size: FPDecimal::must_from_str("1000"),
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let res = app
+        .execute_contract(
+            burner,
+            burn_contract.clone(),
+            &ExecuteMsg::SendNative {
+                asset: Asset {
+                    info: AssetInfo::NativeToken { denom: "lp".to_string() },
+                    amount: Uint128::new(10_500),
+                },
+                callback: None,
+                referrer: None,
+                drive_id: None,
+                idempotency_key: None,
+                deadline: None,
+            },
+            &[Coin::new(10_500u128, "lp")],
+        )
+        .unwrap();
+    assert!(res.has_event(
+        &Event::new("wasm")
+            .add_attribute("action", "burn_swap_proceeds")
+            .add_attribute("unfilled_quantity", "4000")
+    ));
+
+    // The proceeds, the part below a tick and the unfilled part all reach the burn auction
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(3_000u128));
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "lp"), FPDecimal::from(4_500u128));
+    let contract_subaccount = checked_address_to_subaccount_id(&burn_contract, 1);
+    assert_eq!(query_subaccount_balance(&app, contract_subaccount.as_str(), "lp"), FPDecimal::ZERO);
+    assert_eq!(query_subaccount_balance(&app, contract_subaccount.as_str(), "inj"), FPDecimal::ZERO);
+}
+
+#[test]
+fn test_direct_routing_skips_contract_subaccount() {
+    let (mut app, admin, _, burn_contract) = setup(&[("burner", vec![Coin::new(1_000u128, "inj")])]);
+    let burner = app.api().addr_make("burner");
+
+    app.execute_contract(
+        admin,
+        burn_contract.clone(),
+        &ExecuteMsg::SetSubaccountRouting {
+            routing: SubaccountRouting::Direct {},
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        burner.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::SendNative {
+            asset: Asset {
+                info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                amount: Uint128::new(400),
+            },
+            callback: None,
+            referrer: None,
+            drive_id: None,
+            idempotency_key: None,
+            deadline: None,
+        },
+        &[Coin::new(400u128, "inj")],
+    )
+    .unwrap();
+
+    // The contract's bank balance funds the transfer without a deposit in between
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(400u128));
+    let default_subaccount = checked_address_to_subaccount_id(&burn_contract, 0);
+    assert_eq!(query_subaccount_balance(&app, default_subaccount.as_str(), "inj"), FPDecimal::ZERO);
+    assert_eq!(app.wrap().query_balance(&burner, "inj").unwrap().amount, Uint128::new(600));
+    assert_eq!(app.wrap().query_balance(&burn_contract, "inj").unwrap().amount, Uint128::zero());
+}
+
+#[test]
+fn test_cw20_burn_reaches_burn_auction_subaccount() {
+    let (mut app, admin, adapter, burn_contract) = setup(&[("admin", vec![Coin::new(1_000u128, "inj")])]);
+    let burner = app.api().addr_make("burner");
+    let cw20 = store_and_instantiate_cw20(
+        &mut app,
+        &admin,
+        vec![Cw20Coin {
+            address: burner.to_string(),
+            amount: Uint128::new(1_000),
+        }],
+    );
+    let send = |app: &mut InjectiveApp, cw20: &Addr, amount: u128| {
+        app.execute_contract(
+            burner.clone(),
+            cw20.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: burn_contract.to_string(),
+                amount: Uint128::new(amount),
+                msg: Binary::default(),
+            },
+            &[],
+        )
+    };
+
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::FundRegistrationBudget {},
+        &[Coin::new(150u128, "inj")],
+    )
+    .unwrap();
+    let budget = |app: &InjectiveApp| -> Uint128 {
+        app.wrap()
+            .query_wasm_smart(&burn_contract, &QueryMsg::GetRegistrationBudget {})
+            .unwrap()
+    };
+
+    // The budget only pays for CW20s the admin allowlisted, anyone else's token costs nothing
+    let err = send(&mut app, &cw20, 250).unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("is not registered with the adapter and not allowlisted for registration"));
+    assert_eq!(budget(&app), Uint128::new(150));
+    allow_registration(&mut app, &admin, &burn_contract, &cw20);
+    let allowlist: Vec<Addr> = app
+        .wrap()
+        .query_wasm_smart(&burn_contract, &QueryMsg::GetRegistrationAllowlist { start_after: None, limit: None })
+        .unwrap();
+    assert_eq!(allowlist, vec![cw20.clone()]);
+
+    // The first burn registers the token, later ones go straight to the adapter
+    send(&mut app, &cw20, 200).unwrap();
+    send(&mut app, &cw20, 50).unwrap();
+
+    let factory_denom = format!("factory/{}/{}", adapter, cw20);
+    assert_eq!(
+        query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, &factory_denom),
+        FPDecimal::from(250u128)
+    );
+    assert_eq!(app.wrap().query_balance(&burn_contract, &factory_denom).unwrap().amount, Uint128::zero());
+
+    // the adapter keeps the CW20s it converted
+    let balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(&cw20, &Cw20QueryMsg::Balance { address: adapter.to_string() })
+        .unwrap();
+    assert_eq!(balance.balance, Uint128::new(250));
+    let balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(&cw20, &Cw20QueryMsg::Balance { address: burner.to_string() })
+        .unwrap();
+    assert_eq!(balance.balance, Uint128::new(750));
+
+    assert_eq!(budget(&app), Uint128::new(50));
+    assert_eq!(app.wrap().query_balance(&burn_contract, "inj").unwrap().amount, Uint128::new(50));
+    let registrations: Vec<Cw20Registration> = app
+        .wrap()
+        .query_wasm_smart(&burn_contract, &QueryMsg::GetRegistrations { start_after: None, limit: None })
+        .unwrap();
+    assert_eq!(registrations.len(), 1);
+    assert_eq!(registrations[0].cw20, cw20);
+    assert_eq!(registrations[0].fee, Uint128::new(ADAPTER_REGISTRATION_FEE));
+    assert_eq!(registrations[0].triggered_by, burner);
+
+    // What is left does not cover another registration
+    let other_cw20 = store_and_instantiate_cw20(
+        &mut app,
+        &admin,
+        vec![Cw20Coin {
+            address: burner.to_string(),
+            amount: Uint128::new(1_000),
+        }],
+    );
+    let simulation: SimulateBurnResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &burn_contract,
+            &QueryMsg::SimulateBurn {
+                asset: Asset {
+                    info: AssetInfo::Token { contract_addr: other_cw20.to_string() },
+                    amount: Uint128::new(100),
+                },
+            },
+        )
+        .unwrap();
+    assert!(!simulation.allowed);
+    allow_registration(&mut app, &admin, &burn_contract, &other_cw20);
+    let err = send(&mut app, &other_cw20, 100).unwrap_err();
+    assert!(err.root_cause().to_string().contains("registration budget (50inj) cannot cover the fee (100inj)"));
+
+    // Unspent budget can be withdrawn
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::WithdrawRegistrationBudget { amount: Uint128::new(50), recipient: None },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(app.wrap().query_balance(&admin, "inj").unwrap().amount, Uint128::new(900));
+}
+
+#[test]
+fn test_message_backends_reach_same_balances() {
+    let run = |backend: MessageBackend| {
+        let (mut app, admin, adapter, burn_contract) = setup(&[
+            ("admin", vec![Coin::new(1_000u128, "inj")]),
+            ("burner", vec![Coin::new(1_000u128, "inj")]),
+        ]);
+        let burner = app.api().addr_make("burner");
+        let cw20 = store_and_instantiate_cw20(
+            &mut app,
+            &admin,
+            vec![Cw20Coin {
+                address: burner.to_string(),
+                amount: Uint128::new(1_000),
+            }],
+        );
+
+        app.execute_contract(
+            admin.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::SetMessageBackend { backend },
+            &[],
+        )
+        .unwrap();
+        let active: MessageBackend = app
+            .wrap()
+            .query_wasm_smart(&burn_contract, &QueryMsg::GetMessageBackend {})
+            .unwrap();
+        assert_eq!(active, backend);
+        allow_registration(&mut app, &admin, &burn_contract, &cw20);
+        app.execute_contract(
+            admin.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::FundRegistrationBudget {},
+            &[Coin::new(100u128, "inj")],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            burner.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::SendNative {
+                asset: Asset {
+                    info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                    amount: Uint128::new(400),
+                },
+                callback: None,
+                referrer: None,
+                drive_id: None,
+                idempotency_key: None,
+                deadline: None,
+            },
+            &[Coin::new(400u128, "inj")],
+        )
+        .unwrap();
+        app.execute_contract(
+            burner.clone(),
+            cw20.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: burn_contract.to_string(),
+                amount: Uint128::new(250),
+                msg: Binary::default(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let factory_denom = format!("factory/{}/{}", adapter, cw20);
+        let contract_subaccount = checked_address_to_subaccount_id(&burn_contract, 1);
+        (
+            query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"),
+            query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, &factory_denom),
+            query_subaccount_balance(&app, contract_subaccount.as_str(), "inj"),
+            query_subaccount_balance(&app, contract_subaccount.as_str(), &factory_denom),
+            app.wrap().query_balance(&burner, "inj").unwrap().amount,
+        )
+    };
+
+    let custom = run(MessageBackend::Custom);
+    assert_eq!(
+        custom,
+        (
+            FPDecimal::from(400u128),
+            FPDecimal::from(250u128),
+            FPDecimal::ZERO,
+            FPDecimal::ZERO,
+            Uint128::new(600)
+        )
+    );
+    assert_eq!(run(MessageBackend::Protobuf), custom);
+}
+
+#[test]
+fn test_pledges_burn_once_unlocked() {
+    let (mut app, admin, adapter, burn_contract) = setup(&[
+        ("pledger", vec![Coin::new(1_000u128, "inj")]),
+        ("admin", vec![Coin::new(1_000u128, "inj")]),
+    ]);
+    let pledger = app.api().addr_make("pledger");
+    let keeper = app.api().addr_make("keeper");
+    let cw20 = store_and_instantiate_cw20(
+        &mut app,
+        &admin,
+        vec![Cw20Coin {
+            address: pledger.to_string(),
+            amount: Uint128::new(1_000),
+        }],
+    );
+    allow_registration(&mut app, &admin, &burn_contract, &cw20);
+    app.execute_contract(
+        admin,
+        burn_contract.clone(),
+        &ExecuteMsg::FundRegistrationBudget {},
+        &[Coin::new(100u128, "inj")],
+    )
+    .unwrap();
+    let block = app.block_info();
+    let unlock_height = block.height + 100;
+
+    // Native pledge locked in right away, due at a height
+    app.execute_contract(
+        pledger.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::CreatePledge {
+            asset: Asset {
+                info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                amount: Uint128::new(400),
+            },
+            terms: PledgeTerms {
+                unlock: BlockPoint::AtHeight { height: unlock_height },
+                cancellable_until: None,
+            },
+        },
+        &[Coin::new(400u128, "inj")],
+    )
+    .unwrap();
+    // CW20 pledge due at a time, cancellable for an hour
+    app.execute_contract(
+        pledger.clone(),
+        cw20.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: burn_contract.to_string(),
+            amount: Uint128::new(300),
+            msg: to_json_binary(&Cw20HookMsg {
+                callback: None,
+                pledge: Some(PledgeTerms {
+                    unlock: BlockPoint::AtTime { time: block.time.plus_days(1) },
+                    cancellable_until: Some(BlockPoint::AtTime { time: block.time.plus_hours(1) }),
+                }),
+                ..Default::default()
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(app.wrap().query_balance(&burn_contract, "inj").unwrap().amount, Uint128::new(500));
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::ZERO);
+
+    let by_pledger: Vec<Pledge> = app
+        .wrap()
+        .query_wasm_smart(
+            &burn_contract,
+            &QueryMsg::GetPledgesByPledger { pledger: pledger.to_string(), start_after: None, limit: None },
+        )
+        .unwrap();
+    assert_eq!(by_pledger.iter().map(|pledge| pledge.id).collect::<Vec<_>>(), vec![1, 2]);
+    let due = |app: &InjectiveApp, unlocked_only: bool| -> Vec<u64> {
+        let pledges: Vec<Pledge> = app
+            .wrap()
+            .query_wasm_smart(
+                &burn_contract,
+                &QueryMsg::GetPledgesByDue { unlocked_only: Some(unlocked_only), start_after: None, limit: None },
+            )
+            .unwrap();
+        pledges.iter().map(|pledge| pledge.id).collect()
+    };
+    assert_eq!(due(&app, false), vec![1, 2]);
+    assert_eq!(due(&app, true), Vec::<u64>::new());
+    // Paging carries on from height based unlocks into time based ones
+    let next_page: Vec<Pledge> = app
+        .wrap()
+        .query_wasm_smart(
+            &burn_contract,
+            &QueryMsg::GetPledgesByDue { unlocked_only: Some(false), start_after: Some(1), limit: Some(1) },
+        )
+        .unwrap();
+    assert_eq!(next_page.iter().map(|pledge| pledge.id).collect::<Vec<_>>(), vec![2]);
+
+    // Nothing can be burned early and locked in pledges cannot be cancelled
+    let trigger = |app: &mut InjectiveApp, id: u64| {
+        app.execute_contract(keeper.clone(), burn_contract.clone(), &ExecuteMsg::TriggerPledge { id }, &[])
+    };
+    let err = trigger(&mut app, 1).unwrap_err();
+    assert!(err.root_cause().to_string().contains("is locked until"));
+    let err = app
+        .execute_contract(pledger.clone(), burn_contract.clone(), &ExecuteMsg::CancelPledge { id: 1 }, &[])
+        .unwrap_err();
+    assert!(err.root_cause().to_string().contains("Pledge 1 is locked in"));
+
+    // Anyone can burn the native pledge once its height is reached
+    app.update_block(|block| block.height = unlock_height);
+    assert_eq!(due(&app, true), vec![1]);
+    trigger(&mut app, 1).unwrap();
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(400u128));
+    trigger(&mut app, 1).unwrap_err();
+    assert_eq!(due(&app, false), vec![2]);
+
+    // The pledger changes their mind about the CW20 pledge within the hour
+    app.execute_contract(pledger.clone(), burn_contract.clone(), &ExecuteMsg::CancelPledge { id: 2 }, &[])
+        .unwrap();
+    let balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(&cw20, &Cw20QueryMsg::Balance { address: pledger.to_string() })
+        .unwrap();
+    assert_eq!(balance.balance, Uint128::new(1_000));
+    // Closed pledges leave the due index, only actionable ones are left to page through
+    assert_eq!(due(&app, false), Vec::<u64>::new());
+
+    // A CW20 pledge past its lock-in burns through the adapter
+    let block = app.block_info();
+    app.execute_contract(
+        pledger.clone(),
+        cw20.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: burn_contract.to_string(),
+            amount: Uint128::new(300),
+            msg: to_json_binary(&Cw20HookMsg {
+                callback: None,
+                pledge: Some(PledgeTerms {
+                    unlock: BlockPoint::AtTime { time: block.time.plus_days(1) },
+                    cancellable_until: Some(BlockPoint::AtTime { time: block.time.plus_hours(1) }),
+                }),
+                ..Default::default()
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(|block| block.time = block.time.plus_days(1));
+    app.execute_contract(pledger.clone(), burn_contract.clone(), &ExecuteMsg::CancelPledge { id: 3 }, &[])
+        .unwrap_err();
+    trigger(&mut app, 3).unwrap();
+    let factory_denom = format!("factory/{}/{}", adapter, cw20);
+    assert_eq!(
+        query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, &factory_denom),
+        FPDecimal::from(300u128)
+    );
+
+    let pledge: Pledge = app.wrap().query_wasm_smart(&burn_contract, &QueryMsg::GetPledge { id: 3 }).unwrap();
+    assert_eq!(pledge.status, PledgeStatus::Burned);
+    assert_eq!(pledge.closed_at, Some(app.block_info().time));
+    assert_eq!(due(&app, false), Vec::<u64>::new());
+}
+
+#[test]
+fn test_conditional_burns_follow_oracle_price() {
+    let (mut app, _, _, burn_contract) = setup(&[("treasury", vec![Coin::new(1_000u128, "inj")])]);
+    let treasury = app.api().addr_make("treasury");
+    let keeper = app.api().addr_make("keeper");
+    let expires_at = app.block_info().time.plus_days(7);
+    let create = |app: &mut InjectiveApp, comparator: PriceComparator, threshold: u128, amount: u128| {
+        app.execute_contract(
+            treasury.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::CreateConditionalBurn {
+                asset: Asset {
+                    info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                    amount: Uint128::new(amount),
+                },
+                condition: BurnCondition {
+                    source: OracleSource {
+                        oracle_type: OracleType::PriceFeed,
+                        base: "inj".to_string(),
+                        quote: "usdt".to_string(),
+                    },
+                    comparator,
+                    threshold: FPDecimal::from(threshold),
+                    expires_at,
+                },
+            },
+            &[Coin::new(amount, "inj")],
+        )
+    };
+    let page = |app: &InjectiveApp, start_after: Option<u64>, limit: Option<u32>| -> (Vec<u64>, Option<u64>) {
+        let response: TriggerableConditionalBurnsResponse = app
+            .wrap()
+            .query_wasm_smart(&burn_contract, &QueryMsg::GetTriggerableConditionalBurns { start_after, limit })
+            .unwrap();
+        (response.burns.iter().map(|triggerable| triggerable.order.id).collect(), response.last_scanned)
+    };
+    let triggerable = |app: &InjectiveApp| -> Vec<u64> { page(app, None, None).0 };
+    let trigger = |app: &mut InjectiveApp, id: u64| {
+        app.execute_contract(keeper.clone(), burn_contract.clone(), &ExecuteMsg::TriggerConditional { id }, &[])
+    };
+
+    // Orders on a pair without a price are rejected up front
+    let err = create(&mut app, PriceComparator::Above, 30, 300).unwrap_err();
+    assert!(err.root_cause().to_string().contains("No oracle price for inj/usdt"));
+
+    set_oracle_price(&mut app, "inj", "usdt", FPDecimal::from(25u128));
+    create(&mut app, PriceComparator::Above, 30, 300).unwrap();
+    create(&mut app, PriceComparator::Below, 10, 200).unwrap();
+    assert_eq!(triggerable(&app), Vec::<u64>::new());
+    let err = trigger(&mut app, 1).unwrap_err();
+    assert!(err.root_cause().to_string().contains("does not hold at price 25"));
+
+    // Only the order above the threshold fires once the price moves up
+    set_oracle_price(&mut app, "inj", "usdt", FPDecimal::from(31u128));
+    assert_eq!(triggerable(&app), vec![1]);
+    // The limit caps the orders scanned, a keeper pages on with the returned cursor
+    assert_eq!(page(&app, None, Some(1)), (vec![1], Some(1)));
+    assert_eq!(page(&app, Some(1), Some(1)), (vec![], Some(2)));
+    assert_eq!(page(&app, Some(2), Some(1)), (vec![], None));
+    trigger(&mut app, 1).unwrap();
+    // Triggered orders leave the open index and are no longer scanned
+    assert_eq!(page(&app, None, Some(1)), (vec![], Some(2)));
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(300u128));
+    trigger(&mut app, 1).unwrap_err();
+    assert_eq!(triggerable(&app), Vec::<u64>::new());
+
+    // The other order expires unfilled and is refunded to the treasury
+    let refund = |app: &mut InjectiveApp| {
+        app.execute_contract(keeper.clone(), burn_contract.clone(), &ExecuteMsg::RefundConditional { id: 2 }, &[])
+    };
+    let err = refund(&mut app).unwrap_err();
+    assert!(err.root_cause().to_string().contains("cannot be refunded before"));
+    set_oracle_price(&mut app, "inj", "usdt", FPDecimal::from(5u128));
+    app.update_block(|block| block.time = expires_at);
+    assert_eq!(triggerable(&app), Vec::<u64>::new());
+    let err = trigger(&mut app, 2).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Conditional burn 2 has expired"));
+    refund(&mut app).unwrap();
+    assert_eq!(app.wrap().query_balance(&treasury, "inj").unwrap().amount, Uint128::new(700));
+    assert_eq!(app.wrap().query_balance(&burn_contract, "inj").unwrap().amount, Uint128::zero());
+
+    let orders: Vec<ConditionalBurn> = app
+        .wrap()
+        .query_wasm_smart(&burn_contract, &QueryMsg::GetConditionalBurns { start_after: None, limit: None })
+        .unwrap();
+    assert_eq!(
+        orders.iter().map(|order| order.status.clone()).collect::<Vec<_>>(),
+        vec![ConditionalBurnStatus::Burned, ConditionalBurnStatus::Refunded]
+    );
+}
+
+#[test]
+fn test_campaign_matches_burns_from_sponsor_pool() {
+    let (mut app, admin, _, burn_contract) = setup(&[
+        ("sponsor", vec![Coin::new(2_000u128, "inj")]),
+        ("alice", vec![Coin::new(1_000u128, "inj")]),
+        ("bob", vec![Coin::new(1_000u128, "inj")]),
+    ]);
+    let sponsor = app.api().addr_make("sponsor");
+    let alice = app.api().addr_make("alice");
+    let bob = app.api().addr_make("bob");
+    let end_time = app.block_info().time.plus_days(1);
+    let inj = |amount: u128| Asset {
+        info: AssetInfo::NativeToken { denom: "inj".to_string() },
+        amount: Uint128::new(amount),
+    };
+    let terms = CampaignTerms {
+        match_ratio: Decimal::one(),
+        per_user_cap: Uint128::new(300),
+        end_time,
+    };
+    let create = |app: &mut InjectiveApp, pool: u128| {
+        app.execute_contract(
+            sponsor.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::CreateCampaign { pool: inj(pool), terms: terms.clone() },
+            &[Coin::new(pool, "inj")],
+        )
+        .unwrap()
+    };
+    let burn = |app: &mut InjectiveApp, user: &Addr, amount: u128| {
+        app.execute_contract(
+            user.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::SendNative { asset: inj(amount), callback: None, referrer: None, drive_id: None, idempotency_key: None, deadline: None },
+            &[Coin::new(amount, "inj")],
+        )
+        .unwrap()
+    };
+    let campaign = |app: &InjectiveApp, id: u64| -> CampaignResponse {
+        app.wrap().query_wasm_smart(&burn_contract, &QueryMsg::GetCampaign { id }).unwrap()
+    };
+    let try_create = |app: &mut InjectiveApp, pool: u128, terms: &CampaignTerms| {
+        app.execute_contract(
+            sponsor.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::CreateCampaign { pool: inj(pool), terms: terms.clone() },
+            &[Coin::new(pool, "inj")],
+        )
+    };
+
+    // Campaigns are off until the admin sets a minimum pool for the asset
+    let err = try_create(&mut app, 500, &terms).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Campaigns are not enabled for inj"));
+    let set_min_pool = ExecuteMsg::SetCampaignMinPool {
+        asset_info: inj(0).info,
+        amount: Some(Uint128::new(100)),
+    };
+    app.execute_contract(sponsor.clone(), burn_contract.clone(), &set_min_pool, &[]).unwrap_err();
+    app.execute_contract(admin.clone(), burn_contract.clone(), &set_min_pool, &[]).unwrap();
+    let min_pool: CampaignMinPoolResponse = app
+        .wrap()
+        .query_wasm_smart(&burn_contract, &QueryMsg::GetCampaignMinPool { asset_info: inj(0).info })
+        .unwrap();
+    assert_eq!(min_pool.min_pool, Some(Uint128::new(100)));
+    let err = try_create(&mut app, 99, &terms).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Campaign pool must be at least 100"));
+    create(&mut app, 500);
+
+    // Matched one to one until alice reaches her cap
+    burn(&mut app, &alice, 200);
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(400u128));
+    burn(&mut app, &alice, 200);
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(700u128));
+    let alice_match: CampaignMatchResponse = app
+        .wrap()
+        .query_wasm_smart(&burn_contract, &QueryMsg::GetCampaignMatch { id: 1, user: alice.to_string() })
+        .unwrap();
+    assert_eq!(alice_match.matched, Uint128::new(300));
+    assert_eq!(alice_match.remaining_cap, Uint128::zero());
+
+    // Bob only gets what is left of the pool, the matched burn is credited to the sponsor
+    let res = burn(&mut app, &bob, 300);
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(1_200u128));
+    // Every matched burn before bob's is a burn of its own
+    let receipt: BurnReceipt = from_json(res.data.unwrap()).unwrap();
+    assert_eq!(receipt.burn_id, 5);
+    assert_eq!(receipt.payer, bob);
+    let progress = campaign(&app, 1);
+    assert_eq!(progress.campaign.matched, Uint128::new(500));
+    assert_eq!(progress.campaign.participants, 2);
+    assert_eq!(progress.remaining, Uint128::zero());
+    assert!(!progress.running);
+
+    // An unused pool goes back to the sponsor once the campaign ended
+    create(&mut app, 100);
+    let close = |app: &mut InjectiveApp, id: u64| {
+        app.execute_contract(bob.clone(), burn_contract.clone(), &ExecuteMsg::CloseCampaign { id }, &[])
+    };
+    let err = close(&mut app, 2).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Campaign 2 runs until"));
+    app.update_block(|block| block.time = end_time);
+    burn(&mut app, &bob, 100);
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(1_300u128));
+    close(&mut app, 2).unwrap();
+    close(&mut app, 2).unwrap_err();
+    let closed = campaign(&app, 2);
+    assert_eq!(closed.campaign.status, CampaignStatus::Closed);
+    assert_eq!(closed.campaign.refunded, Uint128::new(100));
+    assert_eq!(app.wrap().query_balance(&sponsor, "inj").unwrap().amount, Uint128::new(1_500));
+    assert_eq!(app.wrap().query_balance(&burn_contract, "inj").unwrap().amount, Uint128::zero());
+
+    // Only a few campaigns may run on an asset at once, each one adds a matched burn to every burn
+    let later_terms = CampaignTerms {
+        match_ratio: Decimal::one(),
+        per_user_cap: Uint128::new(100),
+        end_time: end_time.plus_days(1),
+    };
+    for _ in 0..MAX_RUNNING_CAMPAIGNS {
+        try_create(&mut app, 100, &later_terms).unwrap();
+    }
+    let err = try_create(&mut app, 100, &later_terms).unwrap_err();
+    assert!(err.root_cause().to_string().contains("inj already has 5 running campaigns"));
+
+    // Campaigns that ran dry stop counting
+    burn(&mut app, &alice, 100);
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(1_900u128));
+    try_create(&mut app, 100, &later_terms).unwrap();
+
+    // Escrowed burns are not matched, a stream release burns only its own tranche
+    let start_time = app.block_info().time;
+    app.execute_contract(
+        bob.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::CreateStream {
+            asset: inj(200),
+            schedule: StreamSchedule { start_time, end_time: start_time.plus_hours(2), interval: 3_600 },
+        },
+        &[Coin::new(200u128, "inj")],
+    )
+    .unwrap();
+    app.update_block(|block| block.time = start_time.plus_hours(1));
+    app.execute_contract(bob.clone(), burn_contract.clone(), &ExecuteMsg::ReleaseStream { id: 1 }, &[]).unwrap();
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(2_000u128));
+    let untouched = campaign(&app, 8);
+    assert_eq!(untouched.campaign.matched, Uint128::zero());
+    assert_eq!(untouched.remaining, Uint128::new(100));
+}
+
+#[test]
+fn test_referrals_pay_cut_and_track_stats() {
+    let (mut app, admin, adapter, burn_contract) = setup(&[
+        ("alice", vec![Coin::new(1_000u128, "inj")]),
+        ("admin", vec![Coin::new(1_000u128, "inj")]),
+    ]);
+    let alice = app.api().addr_make("alice");
+    let referrer = app.api().addr_make("referrer");
+    let cw20 = store_and_instantiate_cw20(
+        &mut app,
+        &admin,
+        vec![Cw20Coin {
+            address: alice.to_string(),
+            amount: Uint128::new(1_000),
+        }],
+    );
+    allow_registration(&mut app, &admin, &burn_contract, &cw20);
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::FundRegistrationBudget {},
+        &[Coin::new(100u128, "inj")],
+    )
+    .unwrap();
+    let burn = |app: &mut InjectiveApp, amount: u128, referrer: &str| {
+        app.execute_contract(
+            alice.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::SendNative {
+                asset: Asset {
+                    info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                    amount: Uint128::new(amount),
+                },
+                callback: None,
+                referrer: Some(referrer.to_string()),
+                drive_id: None,
+                idempotency_key: None,
+                deadline: None,
+            },
+            &[Coin::new(amount, "inj")],
+        )
+    };
+
+    // Without a configured cut the referral is only counted
+    burn(&mut app, 100, referrer.as_str()).unwrap();
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(100u128));
+
+    let set_cut = |app: &mut InjectiveApp, bps: u16| {
+        app.execute_contract(admin.clone(), burn_contract.clone(), &ExecuteMsg::SetReferralCut { bps }, &[])
+    };
+    let err = set_cut(&mut app, 1_001).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Referral cut cannot exceed 1000 basis points"));
+    set_cut(&mut app, 500).unwrap();
+
+    // 5% of the burn goes to the referrer, the receipt only covers what was burned
+    let res = burn(&mut app, 200, referrer.as_str()).unwrap();
+    let receipt: BurnReceipt = from_json(res.data.unwrap()).unwrap();
+    assert_eq!(receipt.asset.amount, Uint128::new(190));
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(290u128));
+    assert_eq!(app.wrap().query_balance(&referrer, "inj").unwrap().amount, Uint128::new(10));
+    assert_eq!(app.wrap().query_balance(&burn_contract, "inj").unwrap().amount, Uint128::new(100));
+
+    let err = burn(&mut app, 100, alice.as_str()).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Burners cannot refer themselves"));
+    burn(&mut app, 100, "not an address").unwrap_err();
+
+    // CW20 burns pay the cut in the token itself
+    app.execute_contract(
+        alice.clone(),
+        cw20.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: burn_contract.to_string(),
+            amount: Uint128::new(400),
+            msg: to_json_binary(&Cw20HookMsg {
+                referrer: Some(referrer.to_string()),
+                ..Default::default()
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    let factory_denom = format!("factory/{}/{}", adapter, cw20);
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, &factory_denom), FPDecimal::from(380u128));
+    let referrer_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(&cw20, &Cw20QueryMsg::Balance { address: referrer.to_string() })
+        .unwrap();
+    assert_eq!(referrer_balance.balance, Uint128::new(20));
+
+    let stats: Vec<ReferralStats> = app
+        .wrap()
+        .query_wasm_smart(
+            &burn_contract,
+            &QueryMsg::GetReferrerStats { referrer: referrer.to_string(), start_after: None, limit: None },
+        )
+        .unwrap();
+    let native = stats
+        .iter()
+        .find(|stats| stats.asset_info == AssetInfo::NativeToken { denom: "inj".to_string() })
+        .unwrap();
+    assert_eq!((native.burns, native.burned, native.paid), (2, Uint128::new(290), Uint128::new(10)));
+    let token = stats
+        .iter()
+        .find(|stats| stats.asset_info == AssetInfo::Token { contract_addr: cw20.to_string() })
+        .unwrap();
+    assert_eq!((token.burns, token.burned, token.paid), (1, Uint128::new(380), Uint128::new(20)));
+
+    let all: Vec<ReferralStats> = app
+        .wrap()
+        .query_wasm_smart(&burn_contract, &QueryMsg::GetReferralStats { start_after: None, limit: Some(1) })
+        .unwrap();
+    assert_eq!(all, stats[..1]);
+    let rest: Vec<ReferralStats> = app
+        .wrap()
+        .query_wasm_smart(
+            &burn_contract,
+            &QueryMsg::GetReferralStats {
+                start_after: Some((referrer.to_string(), all[0].asset_info.to_string())),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(rest, stats[1..]);
+}
+
+#[test]
+fn test_drives_track_progress_and_announce_goal_once() {
+    let (mut app, admin, _, burn_contract) = setup(&[
+        ("alice", vec![Coin::new(1_000u128, "inj")]),
+        ("bob", vec![Coin::new(1_000u128, "inj")]),
+    ]);
+    let alice = app.api().addr_make("alice");
+    let bob = app.api().addr_make("bob");
+    let now = app.block_info().time;
+    let terms = |start_time, goal: u128| DriveTerms {
+        name: "Burn 500 INJ this week".to_string(),
+        asset_info: AssetInfo::NativeToken { denom: "inj".to_string() },
+        goal: Uint128::new(goal),
+        start_time,
+        end_time: now.plus_days(7),
+    };
+    let create = |app: &mut InjectiveApp, creator: &Addr, terms: DriveTerms| {
+        app.execute_contract(creator.clone(), burn_contract.clone(), &ExecuteMsg::CreateDrive { terms }, &[])
+    };
+    let burn = |app: &mut InjectiveApp, user: &Addr, amount: u128, drive_id: u64| {
+        app.execute_contract(
+            user.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::SendNative {
+                asset: Asset {
+                    info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                    amount: Uint128::new(amount),
+                },
+                callback: None,
+                referrer: None,
+                drive_id: Some(drive_id),
+                idempotency_key: None,
+                deadline: None,
+            },
+            &[Coin::new(amount, "inj")],
+        )
+    };
+    let goal_reached = |res: &AppResponse| res.events.iter().any(|event| event.ty == "wasm-drive_goal_reached");
+
+    // Only the admin creates drives until creation is opened up
+    create(&mut app, &admin, terms(now, 500)).unwrap();
+    let err = create(&mut app, &alice, terms(now.plus_days(1), 100)).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Unauthorized"));
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::SetDriveCreation { permissionless: true },
+        &[],
+    )
+    .unwrap();
+    create(&mut app, &alice, terms(now.plus_days(1), 100)).unwrap();
+    let err = burn(&mut app, &alice, 100, 2).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Drive 2 is not running"));
+    burn(&mut app, &alice, 100, 3).unwrap_err();
+
+    // The goal event fires on the burn crossing the goal and never again
+    assert!(!goal_reached(&burn(&mut app, &alice, 200, 1).unwrap()));
+    assert!(!goal_reached(&burn(&mut app, &bob, 100, 1).unwrap()));
+    assert!(goal_reached(&burn(&mut app, &alice, 250, 1).unwrap()));
+    assert!(!goal_reached(&burn(&mut app, &bob, 50, 1).unwrap()));
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(600u128));
+
+    let progress: DriveResponse = app.wrap().query_wasm_smart(&burn_contract, &QueryMsg::GetDrive { id: 1 }).unwrap();
+    assert_eq!(progress.drive.burned, Uint128::new(600));
+    assert_eq!((progress.drive.burns, progress.drive.contributors), (4, 2));
+    assert_eq!(progress.drive.goal_reached_at, Some(now));
+    assert_eq!(progress.remaining, Uint128::zero());
+    assert!(progress.running);
+
+    let leaderboard = |app: &InjectiveApp, start_after: Option<String>| -> Vec<(Addr, Uint128)> {
+        let contributors: Vec<DriveContribution> = app
+            .wrap()
+            .query_wasm_smart(
+                &burn_contract,
+                &QueryMsg::GetDriveContributors { id: 1, start_after, limit: Some(1) },
+            )
+            .unwrap();
+        contributors.into_iter().map(|c| (c.contributor, c.burned)).collect()
+    };
+    assert_eq!(leaderboard(&app, None), vec![(alice.clone(), Uint128::new(450))]);
+    assert_eq!(leaderboard(&app, Some(alice.to_string())), vec![(bob.clone(), Uint128::new(150))]);
+    assert_eq!(leaderboard(&app, Some(bob.to_string())), vec![]);
+
+    // Past its end a drive no longer counts burns
+    app.update_block(|block| block.time = now.plus_days(7));
+    let err = burn(&mut app, &bob, 10, 1).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Drive 1 is not running"));
+    let drives: Vec<DriveResponse> = app
+        .wrap()
+        .query_wasm_smart(&burn_contract, &QueryMsg::GetDrives { start_after: None, limit: None })
+        .unwrap();
+    assert_eq!(drives.iter().map(|d| (d.drive.id, d.running)).collect::<Vec<_>>(), vec![(1, false), (2, false)]);
+}
+
+#[test]
+fn test_sender_access_modes() {
+    let (mut app, admin, _, burn_contract) = setup(&[
+        ("alice", vec![Coin::new(1_000u128, "inj")]),
+        ("bob", vec![Coin::new(1_000u128, "inj")]),
+        ("admin", vec![Coin::new(1_000u128, "inj")]),
+    ]);
+    let alice = app.api().addr_make("alice");
+    let bob = app.api().addr_make("bob");
+    let cw20 = store_and_instantiate_cw20(
+        &mut app,
+        &admin,
+        vec![
+            Cw20Coin { address: alice.to_string(), amount: Uint128::new(1_000) },
+            Cw20Coin { address: bob.to_string(), amount: Uint128::new(1_000) },
+        ],
+    );
+    allow_registration(&mut app, &admin, &burn_contract, &cw20);
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::FundRegistrationBudget {},
+        &[Coin::new(100u128, "inj")],
+    )
+    .unwrap();
+    let burn_native = |app: &mut InjectiveApp, user: &Addr| {
+        app.execute_contract(
+            user.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::SendNative {
+                asset: Asset {
+                    info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                    amount: Uint128::new(100),
+                },
+                callback: None,
+                referrer: None,
+                drive_id: None,
+                idempotency_key: None,
+                deadline: None,
+            },
+            &[Coin::new(100u128, "inj")],
+        )
+    };
+    let burn_cw20 = |app: &mut InjectiveApp, user: &Addr| {
+        app.execute_contract(
+            user.clone(),
+            cw20.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: burn_contract.to_string(),
+                amount: Uint128::new(100),
+                msg: Binary::default(),
+            },
+            &[],
+        )
+    };
+    let update = |app: &mut InjectiveApp, sender: &Addr, list: SenderList, add: &Addr| {
+        app.execute_contract(
+            sender.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::UpdateSenderList { list, add: vec![add.to_string()], remove: vec![] },
+            &[],
+        )
+    };
+    let set_mode = |app: &mut InjectiveApp, mode: SenderAccess| {
+        app.execute_contract(admin.clone(), burn_contract.clone(), &ExecuteMsg::SetSenderAccess { mode }, &[])
+            .unwrap();
+    };
+    let assert_blocked = |res: AnyResult<AppResponse>, user: &Addr| {
+        let err = res.unwrap_err();
+        assert!(err.root_cause().to_string().contains(&format!("Sender {} is not allowed to burn", user)));
+    };
+
+    let err = update(&mut app, &alice, SenderList::Blocklist, &alice).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Unauthorized"));
+    update(&mut app, &admin, SenderList::Blocklist, &alice).unwrap();
+    update(&mut app, &admin, SenderList::Allowlist, &bob).unwrap();
+
+    // Lists only apply once their mode is active
+    burn_native(&mut app, &alice).unwrap();
+
+    set_mode(&mut app, SenderAccess::Blocklist);
+    assert_blocked(burn_native(&mut app, &alice), &alice);
+    assert_blocked(burn_cw20(&mut app, &alice), &alice);
+    burn_native(&mut app, &bob).unwrap();
+    let access: SenderAccessResponse = app
+        .wrap()
+        .query_wasm_smart(&burn_contract, &QueryMsg::GetSenderAccess { sender: Some(alice.to_string()) })
+        .unwrap();
+    assert_eq!(access, SenderAccessResponse { mode: SenderAccess::Blocklist, sender_allowed: Some(false) });
+
+    // Only bob is allowed, the token contract relaying his CW20s does not have to be
+    set_mode(&mut app, SenderAccess::Allowlist);
+    assert_blocked(burn_native(&mut app, &alice), &alice);
+    assert_blocked(burn_cw20(&mut app, &alice), &alice);
+    burn_native(&mut app, &bob).unwrap();
+    burn_cw20(&mut app, &bob).unwrap();
+    let err = app
+        .execute_contract(
+            alice.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::CreateStream {
+                asset: Asset {
+                    info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                    amount: Uint128::new(100),
+                },
+                schedule: StreamSchedule {
+                    start_time: app.block_info().time,
+                    end_time: app.block_info().time.plus_days(1),
+                    interval: 3_600,
+                },
+            },
+            &[Coin::new(100u128, "inj")],
+        )
+        .unwrap_err();
+    assert!(err.root_cause().to_string().contains("is not allowed to burn"));
+
+    let allowlist: Vec<Addr> = app
+        .wrap()
+        .query_wasm_smart(
+            &burn_contract,
+            &QueryMsg::GetSenderList { list: SenderList::Allowlist, start_after: None, limit: None },
+        )
+        .unwrap();
+    assert_eq!(allowlist, vec![bob.clone()]);
+
+    set_mode(&mut app, SenderAccess::Open);
+    burn_cw20(&mut app, &alice).unwrap();
+}
+
+#[test]
+fn test_idempotency_keys_and_deadlines() {
+    let (mut app, admin, _, burn_contract) = setup(&[
+        ("alice", vec![Coin::new(1_000u128, "inj")]),
+        ("bob", vec![Coin::new(1_000u128, "inj")]),
+        ("admin", vec![Coin::new(1_000u128, "inj")]),
+    ]);
+    let alice = app.api().addr_make("alice");
+    let bob = app.api().addr_make("bob");
+    let cw20 = store_and_instantiate_cw20(
+        &mut app,
+        &admin,
+        vec![Cw20Coin {
+            address: alice.to_string(),
+            amount: Uint128::new(1_000),
+        }],
+    );
+    allow_registration(&mut app, &admin, &burn_contract, &cw20);
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::FundRegistrationBudget {},
+        &[Coin::new(100u128, "inj")],
+    )
+    .unwrap();
+    let burn_native = |app: &mut InjectiveApp, user: &Addr, key: &str, deadline: Option<BlockPoint>| {
+        app.execute_contract(
+            user.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::SendNative {
+                asset: Asset {
+                    info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                    amount: Uint128::new(100),
+                },
+                callback: None,
+                referrer: None,
+                drive_id: None,
+                idempotency_key: Some(key.to_string()),
+                deadline,
+            },
+            &[Coin::new(100u128, "inj")],
+        )
+    };
+    let burn_cw20 = |app: &mut InjectiveApp, key: &str, deadline: Option<BlockPoint>| {
+        app.execute_contract(
+            alice.clone(),
+            cw20.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: burn_contract.to_string(),
+                amount: Uint128::new(100),
+                msg: to_json_binary(&Cw20HookMsg {
+                    idempotency_key: Some(key.to_string()),
+                    deadline,
+                    ..Default::default()
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+    };
+    let key_used = |app: &InjectiveApp, sender: &Addr, key: &str| -> bool {
+        let res: IdempotencyKeyResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &burn_contract,
+                &QueryMsg::GetIdempotencyKey { sender: sender.to_string(), key: key.to_string() },
+            )
+            .unwrap();
+        res.used
+    };
+    let block = app.block_info();
+
+    // A retried request is rejected, keys are scoped per sender and shared by native and CW20 burns
+    burn_native(&mut app, &alice, "order-1", None).unwrap();
+    assert!(key_used(&app, &alice, "order-1"));
+    let err = burn_native(&mut app, &alice, "order-1", None).unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains(&format!("Idempotency key order-1 was already used at height {}", block.height)));
+    burn_cw20(&mut app, "order-1", None).unwrap_err();
+    assert!(!key_used(&app, &bob, "order-1"));
+    burn_native(&mut app, &bob, "order-1", None).unwrap();
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, "inj"), FPDecimal::from(200u128));
+
+    // Requests are accepted up to their deadline, a refused one does not use up its key
+    burn_native(&mut app, &alice, "order-2", Some(BlockPoint::AtHeight { height: block.height })).unwrap();
+    app.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(5);
+    });
+    let err = burn_native(&mut app, &alice, "order-3", Some(BlockPoint::AtHeight { height: block.height })).unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains(&format!("Request deadline height:{} has passed", block.height)));
+    assert!(!key_used(&app, &alice, "order-3"));
+    let err = burn_cw20(&mut app, "order-3", Some(BlockPoint::AtTime { time: block.time })).unwrap_err();
+    assert!(err.root_cause().to_string().contains("has passed"));
+    burn_cw20(&mut app, "order-3", Some(BlockPoint::AtTime { time: block.time.plus_seconds(5) })).unwrap();
+    assert!(key_used(&app, &alice, "order-3"));
+}
+
+#[test]
+fn test_burn_with_permit_relayed() {
+    let (mut app, admin, adapter, burn_contract) = setup(&[
+        ("relayer", vec![Coin::new(1_000u128, "inj")]),
+        ("admin", vec![Coin::new(1_000u128, "inj")]),
+    ]);
+    let relayer = app.api().addr_make("relayer");
+    let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+    let owner = permit_signer(app.api(), &key);
+    let cw20 = store_and_instantiate_cw20(
+        &mut app,
+        &admin,
+        vec![Cw20Coin {
+            address: owner.to_string(),
+            amount: Uint128::new(1_000),
+        }],
+    );
+    allow_registration(&mut app, &admin, &burn_contract, &cw20);
+    app.execute_contract(
+        admin.clone(),
+        burn_contract.clone(),
+        &ExecuteMsg::FundRegistrationBudget {},
+        &[Coin::new(100u128, "inj")],
+    )
+    .unwrap();
+    // Multitest charges no gas, so the owner grants the allowance without holding any INJ
+    app.execute_contract(
+        owner.clone(),
+        cw20.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: burn_contract.to_string(),
+            amount: Uint128::new(600),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let block = app.block_info();
+    let permit = |nonce: u64, amount: u128| BurnPermit {
+        token: cw20.to_string(),
+        amount: Uint128::new(amount),
+        nonce,
+        expiry: BlockPoint::AtHeight { height: block.height + 10 },
+        chain_id: block.chain_id.clone(),
+        contract_addr: burn_contract.to_string(),
+    };
+    let relay = |app: &mut InjectiveApp, permit: BurnPermit, signed: &BurnPermit| {
+        let (signature, public_key) = sign_permit(&key, signed);
+        app.execute_contract(
+            relayer.clone(),
+            burn_contract.clone(),
+            &ExecuteMsg::BurnWithPermit { permit, signature, public_key },
+            &[],
+        )
+    };
+    let nonce = |app: &InjectiveApp| -> u64 {
+        let res: PermitNonceResponse = app
+            .wrap()
+            .query_wasm_smart(&burn_contract, &QueryMsg::GetPermitNonce { owner: owner.to_string() })
+            .unwrap();
+        res.nonce
+    };
+    let factory_denom = format!("factory/{}/{}", adapter, cw20);
+
+    // The relayer burns the owner's tokens and pays nothing but gas
+    relay(&mut app, permit(0, 400), &permit(0, 400)).unwrap();
+    assert_eq!(nonce(&app), 1);
+    assert_eq!(query_subaccount_balance(&app, BURN_AUCTION_SUBACCOUNT, &factory_denom), FPDecimal::from(400u128));
+    let balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(&cw20, &Cw20QueryMsg::Balance { address: owner.to_string() })
+        .unwrap();
+    assert_eq!(balance.balance, Uint128::new(600));
+    assert_eq!(app.wrap().query_balance(&relayer, "inj").unwrap().amount, Uint128::new(1_000));
+
+    // A permit cannot be replayed, tampered with, or used on another chain
+    let err = relay(&mut app, permit(0, 400), &permit(0, 400)).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Invalid permit nonce 0, expected 1"));
+    let err = relay(&mut app, permit(1, 200), &permit(1, 100)).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Invalid permit signature"));
+    let mut other_chain = permit(1, 100);
+    other_chain.chain_id = "injective-1".to_string();
+    let err = relay(&mut app, other_chain.clone(), &other_chain).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Permit is for chain injective-1"));
+
+    // Nothing is pulled beyond the allowance, and expired permits are refused
+    let err = relay(&mut app, permit(1, 300), &permit(1, 300)).unwrap_err();
+    assert!(err.root_cause().to_string().contains("Cannot Sub"));
+    assert_eq!(nonce(&app), 1);
+    app.update_block(|block| block.height += 11);
+    let err = relay(&mut app, permit(1, 100), &permit(1, 100)).unwrap_err();
+    assert!(err.root_cause().to_string().contains(&format!("Permit expired at height:{}", block.height + 10)));
+}
@@ -0,0 +1,113 @@
+use cosmwasm_std::{
+    to_json_binary, to_json_vec, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, SubMsg, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use injective_cosmwasm::InjectiveMsgWrapper;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+use crate::contract::burn_now;
+use crate::msg::PermitNonceResponse;
+use crate::state::{Asset, AssetInfo, BurnPermit, PERMIT_NONCES};
+
+const PUBLIC_KEY_LENGTH: usize = 65;
+
+/// Hash the signer signs: sha256 of the JSON encoded permit, fields in declaration order
+pub fn permit_digest(permit: &BurnPermit) -> StdResult<[u8; 32]> {
+    Ok(Sha256::digest(to_json_vec(permit)?).into())
+}
+
+/// Injective address of an uncompressed secp256k1 key, the last 20 bytes of the keccak256
+/// hash of the key without its 0x04 prefix, as for Ethereum accounts
+pub fn permit_owner(deps: Deps, public_key: &[u8]) -> StdResult<Addr> {
+    if public_key.len() != PUBLIC_KEY_LENGTH || public_key[0] != 0x04 {
+        return Err(StdError::generic_err(
+            "Permit public key must be an uncompressed 65 byte secp256k1 key",
+        ));
+    }
+    let hash = Keccak256::digest(&public_key[1..]);
+    deps.api.addr_humanize(&CanonicalAddr::from(&hash[12..]))
+}
+
+/// Burns the tokens of a permit on behalf of its signer. The signature, chain, contract, expiry
+/// and nonce are checked before the tokens are pulled from the signer's allowance.
+pub fn burn_with_permit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    permit: BurnPermit,
+    signature: &[u8],
+    public_key: &[u8],
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    // Only the permit's CW20 is burned, attached coins would stay in the contract
+    if let Some(coin) = info.funds.first() {
+        return Err(StdError::generic_err(format!("Unexpected funds provided: {}", coin)));
+    }
+    let owner = permit_owner(deps.as_ref(), public_key)?;
+    if !deps.api.secp256k1_verify(&permit_digest(&permit)?, signature, public_key)? {
+        return Err(StdError::generic_err("Invalid permit signature"));
+    }
+    if permit.chain_id != env.block.chain_id {
+        return Err(StdError::generic_err(format!("Permit is for chain {}", permit.chain_id)));
+    }
+    if permit.contract_addr != env.contract.address.as_str() {
+        return Err(StdError::generic_err(format!(
+            "Permit is for contract {}",
+            permit.contract_addr
+        )));
+    }
+    if permit.expiry.is_passed(&env.block) {
+        return Err(StdError::generic_err(format!("Permit expired at {}", permit.expiry)));
+    }
+    if permit.amount.is_zero() {
+        return Err(StdError::generic_err("Permit amount must be greater than zero"));
+    }
+
+    let nonce = PERMIT_NONCES.may_load(deps.storage, &owner)?.unwrap_or_default();
+    if permit.nonce != nonce {
+        return Err(StdError::generic_err(format!(
+            "Invalid permit nonce {}, expected {}",
+            permit.nonce, nonce
+        )));
+    }
+    PERMIT_NONCES.save(deps.storage, &owner, &(nonce + 1))?;
+
+    let token = deps.api.addr_validate(&permit.token)?;
+    let mut messages: Vec<SubMsg<InjectiveMsgWrapper>> = vec![];
+    // Pull the tokens in first, the adapter send below spends them from our balance
+    messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+            owner: owner.to_string(),
+            recipient: env.contract.address.to_string(),
+            amount: permit.amount,
+        })?,
+        funds: vec![],
+    })));
+
+    let asset = Asset {
+        info: AssetInfo::Token {
+            contract_addr: token.to_string(),
+        },
+        amount: permit.amount,
+    };
+    let mut events = vec![];
+    let receipt = burn_now(deps, &env, &info.sender, &owner, asset, &mut messages, &mut events)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_events(events)
+        .set_data(to_json_binary(&receipt)?)
+        .add_attribute("action", "burn_with_permit")
+        .add_attribute("owner", owner)
+        .add_attribute("relayer", info.sender)
+        .add_attribute("nonce", nonce.to_string()))
+}
+
+pub fn query_permit_nonce(deps: Deps, owner: String) -> StdResult<PermitNonceResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    Ok(PermitNonceResponse {
+        nonce: PERMIT_NONCES.may_load(deps.storage, &owner)?.unwrap_or_default(),
+    })
+}
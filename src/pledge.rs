@@ -0,0 +1,239 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError, StdResult,
+    Storage, SubMsg,
+};
+use cw_storage_plus::Bound;
+use injective_cosmwasm::InjectiveMsgWrapper;
+
+use crate::contract::burn_now;
+use crate::escrow::{assert_native_escrow, lock_escrow, refund_escrow, release_escrow};
+use crate::state::{pledges, Asset, BlockPoint, Pledge, PledgeStatus, PledgeTerms, PLEDGES_DUE, PLEDGE_COUNT};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+fn due_key(pledge: &Pledge) -> (u8, u64, u64) {
+    let (kind, value) = pledge.terms.unlock.index_key();
+    (kind, value, pledge.id)
+}
+
+/// Takes a pledge that is no longer pending off the due index
+fn close_pledge(storage: &mut dyn Storage, pledge: &Pledge) -> StdResult<()> {
+    pledges().save(storage, pledge.id, pledge)?;
+    PLEDGES_DUE.remove(storage, due_key(pledge));
+    Ok(())
+}
+
+/// Escrows native coins attached as funds, exactly `asset` has to be attached
+pub fn create_native_pledge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    terms: PledgeTerms,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_native_escrow(&info, &asset)?;
+
+    create_pledge(deps, env, info.sender, asset, terms)
+}
+
+/// Records a pledge for an asset this contract already holds
+pub fn create_pledge(
+    deps: DepsMut,
+    env: Env,
+    pledger: Addr,
+    asset: Asset,
+    terms: PledgeTerms,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    if asset.amount.is_zero() {
+        return Err(StdError::generic_err("Pledge amount must be greater than zero"));
+    }
+    if terms.unlock.is_reached(&env.block) {
+        return Err(StdError::generic_err(format!("Pledge unlock {} has already passed", terms.unlock)));
+    }
+    if let Some(cancellable_until) = &terms.cancellable_until {
+        if cancellable_until.is_reached(&env.block) {
+            return Err(StdError::generic_err(format!(
+                "Pledge lock-in {} has already passed",
+                cancellable_until
+            )));
+        }
+        // Points of different kinds cannot be compared, cancellation still stops at the unlock
+        let (kind, value) = cancellable_until.index_key();
+        let (unlock_kind, unlock_value) = terms.unlock.index_key();
+        if kind == unlock_kind && value > unlock_value {
+            return Err(StdError::generic_err("Pledge lock-in cannot be later than its unlock"));
+        }
+    }
+
+    let id = PLEDGE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    PLEDGE_COUNT.save(deps.storage, &id)?;
+
+    let pledge = Pledge {
+        id,
+        pledger,
+        asset,
+        terms,
+        created_at: env.block.time,
+        status: PledgeStatus::Pending,
+        closed_at: None,
+    };
+    pledges().save(deps.storage, id, &pledge)?;
+    PLEDGES_DUE.save(deps.storage, due_key(&pledge), &Empty {})?;
+    lock_escrow(deps.storage, &pledge.asset)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "create_pledge")
+        .add_attribute("pledge_id", id.to_string())
+        .add_attribute("pledger", pledge.pledger)
+        .add_attribute("asset", pledge.asset.to_string())
+        .add_attribute("unlock", pledge.terms.unlock.to_string()))
+}
+
+/// Burns an unlocked pledge, callable by anyone
+pub fn trigger_pledge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let mut pledge = load_pending_pledge(deps.as_ref(), id)?;
+    if !pledge.terms.unlock.is_reached(&env.block) {
+        return Err(StdError::generic_err(format!(
+            "Pledge {} is locked until {}",
+            id, pledge.terms.unlock
+        )));
+    }
+
+    pledge.status = PledgeStatus::Burned;
+    pledge.closed_at = Some(env.block.time);
+    close_pledge(deps.storage, &pledge)?;
+    release_escrow(deps.storage, &pledge.asset)?;
+
+    let mut messages: Vec<SubMsg<InjectiveMsgWrapper>> = vec![];
+    let mut events = vec![];
+    let receipt = burn_now(deps, &env, &info.sender, &pledge.pledger, pledge.asset, &mut messages, &mut events)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_events(events)
+        .set_data(to_json_binary(&receipt)?)
+        .add_attribute("action", "trigger_pledge")
+        .add_attribute("pledge_id", id.to_string())
+        .add_attribute("pledger", pledge.pledger)
+        .add_attribute("triggered_by", info.sender))
+}
+
+/// Refunds a pledge to its pledger before it is locked in
+pub fn cancel_pledge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let mut pledge = load_pending_pledge(deps.as_ref(), id)?;
+    if info.sender != pledge.pledger {
+        return Err(StdError::generic_err("Unauthorized"));
+    }
+    let cancellable = match &pledge.terms.cancellable_until {
+        Some(cancellable_until) => {
+            !cancellable_until.is_reached(&env.block) && !pledge.terms.unlock.is_reached(&env.block)
+        }
+        None => false,
+    };
+    if !cancellable {
+        return Err(StdError::generic_err(format!("Pledge {} is locked in", id)));
+    }
+
+    pledge.status = PledgeStatus::Cancelled;
+    pledge.closed_at = Some(env.block.time);
+    close_pledge(deps.storage, &pledge)?;
+    release_escrow(deps.storage, &pledge.asset)?;
+
+    let refund = refund_escrow(pledge.asset, pledge.pledger.clone())?;
+
+    Ok(Response::new()
+        .add_message(refund)
+        .add_attribute("action", "cancel_pledge")
+        .add_attribute("pledge_id", id.to_string())
+        .add_attribute("pledger", pledge.pledger))
+}
+
+fn load_pending_pledge(deps: Deps, id: u64) -> StdResult<Pledge> {
+    let pledge = pledges().load(deps.storage, id)?;
+    if pledge.status != PledgeStatus::Pending {
+        return Err(StdError::generic_err(format!("Pledge {} is no longer pending", id)));
+    }
+    Ok(pledge)
+}
+
+pub fn query_pledge(deps: Deps, id: u64) -> StdResult<Binary> {
+    to_json_binary(&pledges().load(deps.storage, id)?)
+}
+
+/// Pledges of `pledger` in any status, ordered by id
+pub fn query_pledges_by_pledger(
+    deps: Deps,
+    pledger: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let pledger = deps.api.addr_validate(&pledger)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let list = pledges()
+        .idx
+        .pledger
+        .prefix(pledger)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, pledge)| pledge))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&list)
+}
+
+/// Pending pledges ordered by unlock point, height based ones first. With `unlocked_only`
+/// only those that can be triggered at the current block are returned.
+pub fn query_pledges_by_due(
+    deps: Deps,
+    env: Env,
+    unlocked_only: bool,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = match start_after {
+        Some(id) => Some(due_key(&pledges().load(deps.storage, id)?)),
+        None => None,
+    };
+
+    // One pass per kind of unlock point, height based ones sort first. Unlocked pledges of a
+    // kind are the ones due up to the current block, so nothing still locked is read.
+    let now = [
+        BlockPoint::AtHeight { height: env.block.height },
+        BlockPoint::AtTime { time: env.block.time },
+    ];
+    let mut list = vec![];
+    for point in now {
+        let (kind, current) = point.index_key();
+        let min = match start {
+            Some((start_kind, _, _)) if start_kind > kind => continue,
+            Some((start_kind, value, id)) if start_kind == kind => Some(Bound::exclusive((value, id))),
+            _ => None,
+        };
+        let max = unlocked_only.then(|| Bound::inclusive((current, u64::MAX)));
+        for key in PLEDGES_DUE
+            .sub_prefix(kind)
+            .keys(deps.storage, min, max, Order::Ascending)
+            .take(limit - list.len())
+        {
+            let (_, id) = key?;
+            list.push(pledges().load(deps.storage, id)?);
+        }
+        if list.len() == limit {
+            break;
+        }
+    }
+    to_json_binary(&list)
+}
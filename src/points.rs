@@ -0,0 +1,146 @@
+use cosmwasm_std::{
+    to_json_binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response, StdError, StdResult, SubMsg,
+    WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, MinterResponse};
+use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
+use cw_utils::parse_instantiate_response_data;
+use injective_cosmwasm::InjectiveMsgWrapper;
+
+use crate::contract::assert_admin;
+use crate::msg::{submsg_response_data, PointsConfigResponse};
+use crate::state::{AssetInfo, BurnDetails, POINTS_RATES, POINTS_TOKEN};
+
+pub const POINTS_REPLY_ID: u64 = 4;
+
+/// Instantiates the points token with this contract as its only minter. The code has to be the
+/// `burn-points` cw20, which rejects transfers, sends and allowances so points stay with whoever burned.
+pub fn create_points_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    code_id: u64,
+    name: String,
+    symbol: String,
+    decimals: u8,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let config = assert_admin(deps.as_ref(), &info)?;
+
+    let instantiate_msg = WasmMsg::Instantiate {
+        admin: Some(config.admin),
+        code_id,
+        msg: to_json_binary(&Cw20InstantiateMsg {
+            name,
+            symbol,
+            decimals,
+            initial_balances: vec![],
+            mint: Some(MinterResponse {
+                minter: env.contract.address.to_string(),
+                cap: None,
+            }),
+            marketing: None,
+        })?,
+        funds: vec![],
+        label: "burn points".to_string(),
+    };
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(instantiate_msg, POINTS_REPLY_ID))
+        .add_attribute("action", "create_points_token")
+        .add_attribute("code_id", code_id.to_string()))
+}
+
+pub fn handle_points_reply(deps: DepsMut, msg: Reply) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let response = msg.result.into_result().map_err(StdError::generic_err)?;
+    let data = submsg_response_data(&response)
+        .ok_or_else(|| StdError::generic_err("Points token instantiation returned no data"))?;
+    let res = parse_instantiate_response_data(&data)
+        .map_err(|e| StdError::generic_err(format!("Failed to parse points token instantiation: {}", e)))?;
+    POINTS_TOKEN.save(deps.storage, &res.contract_address)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "points_token_created")
+        .add_attribute("contract", res.contract_address))
+}
+
+/// Uses an existing points token, this contract has to be its minter. `None` stops minting.
+pub fn set_points_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: Option<String>,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let contract = match contract {
+        Some(contract) => {
+            let contract = deps.api.addr_validate(&contract)?.to_string();
+            POINTS_TOKEN.save(deps.storage, &contract)?;
+            contract
+        }
+        None => {
+            POINTS_TOKEN.remove(deps.storage);
+            "none".to_string()
+        }
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "set_points_token")
+        .add_attribute("contract", contract))
+}
+
+pub fn set_points_rate(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    rate: Option<Decimal>,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let key = asset_info.to_string();
+    match rate {
+        Some(rate) => POINTS_RATES.save(deps.storage, &key, &rate)?,
+        None => POINTS_RATES.remove(deps.storage, &key),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_points_rate")
+        .add_attribute("asset", key)
+        .add_attribute("rate", rate.map_or("none".to_string(), |rate| rate.to_string())))
+}
+
+/// Mints `amount * rate` points to the beneficiary, rounded down. Assets without a rate earn nothing.
+pub fn points_mint_message(deps: Deps, burn: &BurnDetails) -> StdResult<Option<SubMsg<InjectiveMsgWrapper>>> {
+    let token = match POINTS_TOKEN.may_load(deps.storage)? {
+        Some(token) => token,
+        None => return Ok(None),
+    };
+    let rate = match POINTS_RATES.may_load(deps.storage, &burn.asset.info.to_string())? {
+        Some(rate) => rate,
+        None => return Ok(None),
+    };
+
+    let points = burn.asset.amount.mul_floor(rate);
+    if points.is_zero() {
+        return Ok(None);
+    }
+
+    Ok(Some(SubMsg::new(WasmMsg::Execute {
+        contract_addr: token,
+        msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+            recipient: burn.beneficiary.to_string(),
+            amount: points,
+        })?,
+        funds: vec![],
+    })))
+}
+
+pub fn query_points_config(deps: Deps) -> StdResult<PointsConfigResponse> {
+    let rates = POINTS_RATES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PointsConfigResponse {
+        token: POINTS_TOKEN.may_load(deps.storage)?,
+        rates,
+    })
+}
@@ -1,10 +1,16 @@
 
 use cosmwasm_std::{
     to_json_binary, Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, QuerierWrapper,
-    QueryRequest, StdResult, Uint128, WasmQuery,
+    QueryRequest, StdError, StdResult, Uint128, WasmQuery,
 };
 
 use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+use injective_cosmwasm::exchange::types::{Deposit, SubaccountId};
+use injective_cosmwasm::{InjectiveQuerier, InjectiveQueryWrapper};
+use injective_math::FPDecimal;
+use injective_std::types::injective::auction::v1beta1::AuctionQuerier;
+
+use crate::state::OracleSource;
 
 pub fn query_balance(
     querier: &QuerierWrapper,
@@ -19,6 +25,7 @@ pub fn query_balance(
     Ok(balance.amount.amount)
 }
 
+#[allow(deprecated)]
 pub fn query_all_balances(querier: &QuerierWrapper, account_addr: Addr) -> StdResult<Vec<Coin>> {
     // load price form the oracle
     let all_balances: AllBalanceResponse =
@@ -56,3 +63,35 @@ pub fn query_token_info(
     Ok(token_info)
 }
 
+pub fn query_subaccount_deposit(
+    querier: &QuerierWrapper,
+    subaccount_id: &SubaccountId,
+    denom: &String,
+) -> StdResult<Deposit> {
+    // the contract entry points use the default querier, re-wrap it for the exchange module
+    let injective_querier = QuerierWrapper::<InjectiveQueryWrapper>::new(&**querier);
+    let res = InjectiveQuerier::new(&injective_querier).query_subaccount_deposit(subaccount_id, denom)?;
+
+    Ok(res.deposits)
+}
+
+pub fn query_auction_round(querier: &QuerierWrapper) -> StdResult<u64> {
+    let basket = AuctionQuerier::new(querier).current_auction_basket()?;
+
+    Ok(basket.auctionRound)
+}
+
+/// Current pair price of `source` as reported by the oracle module
+pub fn query_oracle_price(querier: &QuerierWrapper, source: &OracleSource) -> StdResult<FPDecimal> {
+    let injective_querier = QuerierWrapper::<InjectiveQueryWrapper>::new(&**querier);
+    let res = InjectiveQuerier::new(&injective_querier).query_oracle_price(
+        &source.oracle_type,
+        &source.base,
+        &source.quote,
+        None,
+    )?;
+
+    res.price_pair_state
+        .map(|state| state.pair_price)
+        .ok_or_else(|| StdError::generic_err(format!("No oracle price for {}/{}", source.base, source.quote)))
+}
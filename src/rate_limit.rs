@@ -0,0 +1,286 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError, StdResult,
+    Uint128,
+};
+use cw_storage_plus::Bound;
+use injective_cosmwasm::InjectiveMsgWrapper;
+
+use crate::contract::assert_admin;
+use crate::error::ContractError;
+use crate::msg::{RateLimitResponse, SenderRateLimitResponse};
+use crate::state::{Asset, AssetInfo, BlockActivity, RateLimit, RATE_LIMIT, RATE_LIMIT_EXEMPT, SENDER_ACTIVITY};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
+
+/// Replaces the limits, `None` stops rate limiting. Recorded activity is kept and measured
+/// against the new window.
+pub fn set_rate_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    limit: Option<RateLimit>,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let Some(limit) = limit else {
+        RATE_LIMIT.remove(deps.storage);
+        return Ok(Response::new()
+            .add_attribute("action", "set_rate_limit")
+            .add_attribute("enabled", "false"));
+    };
+    if limit.window_blocks == 0 {
+        return Err(StdError::generic_err("Rate limit window must be at least one block"));
+    }
+    if limit.max_burns == Some(0) {
+        return Err(StdError::generic_err("Rate limit must allow at least one burn"));
+    }
+    for (i, max) in limit.max_amounts.iter().enumerate() {
+        if max.amount.is_zero() {
+            return Err(StdError::generic_err(format!("Zero rate limit for {}", max.info)));
+        }
+        if limit.max_amounts[..i].iter().any(|other| other.info.equal(&max.info)) {
+            return Err(StdError::generic_err(format!("Duplicate rate limit for {}", max.info)));
+        }
+    }
+    RATE_LIMIT.save(deps.storage, &limit)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_rate_limit")
+        .add_attribute("enabled", "true")
+        .add_attribute("window_blocks", limit.window_blocks.to_string()))
+}
+
+pub fn set_rate_limit_exemption(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    exempt: bool,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    if exempt {
+        RATE_LIMIT_EXEMPT.save(deps.storage, &address, &Empty {})?;
+        // Nothing is tracked for exempt senders, stale activity would only linger
+        let tokens = SENDER_ACTIVITY
+            .prefix(&address)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for token in tokens {
+            SENDER_ACTIVITY.remove(deps.storage, (&address, &token));
+        }
+    } else {
+        RATE_LIMIT_EXEMPT.remove(deps.storage, &address);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_rate_limit_exemption")
+        .add_attribute("address", address)
+        .add_attribute("exempt", exempt.to_string()))
+}
+
+/// Activity key of a sender, see `check_rate_limit` for why CW20 burns are kept apart per token
+fn activity_key<'a>(sender: &'a Addr, token: Option<&'a Addr>) -> (&'a Addr, &'a str) {
+    (sender, token.map(Addr::as_str).unwrap_or_default())
+}
+
+/// Activity of `sender` still inside the window at `height`, older blocks are pruned
+fn window_activity(
+    deps: Deps,
+    limit: &RateLimit,
+    sender: &Addr,
+    token: Option<&Addr>,
+    height: u64,
+) -> StdResult<Vec<BlockActivity>> {
+    let mut activity = SENDER_ACTIVITY.may_load(deps.storage, activity_key(sender, token))?.unwrap_or_default();
+    activity.retain(|block| block.height + limit.window_blocks > height);
+    Ok(activity)
+}
+
+fn block_amount(block: &BlockActivity, info: &AssetInfo) -> Uint128 {
+    block
+        .amounts
+        .iter()
+        .filter(|amount| amount.info.equal(info))
+        .map(|amount| amount.amount)
+        .sum()
+}
+
+/// First height at which enough of the oldest activity has left the window to free up `excess`
+fn retry_height(
+    activity: &[BlockActivity],
+    window_blocks: u64,
+    excess: Uint128,
+    weight: impl Fn(&BlockActivity) -> Uint128,
+) -> u64 {
+    let mut freed = Uint128::zero();
+    for block in activity {
+        freed += weight(block);
+        if freed >= excess {
+            return block.height + window_blocks;
+        }
+    }
+    // Callers never ask for more than the window holds, the newest block frees everything
+    activity.last().map(|block| block.height + window_blocks).unwrap_or_default()
+}
+
+/// Counts incoming burns against the limits of `sender` and records them. Fails with the
+/// height the sender can try again at when a limit would be exceeded.
+///
+/// `token` is the CW20 contract relaying a `Receive`. The sender of a `Receive` is whatever the
+/// token contract claims, so any contract could name a victim and use up their window. CW20
+/// burns are therefore counted per token and sender: a token can only ever limit the burns it
+/// relays itself, and the limits of a sender's own messages are never touched by it.
+pub fn check_rate_limit(
+    deps: DepsMut,
+    env: &Env,
+    sender: &Addr,
+    token: Option<&Addr>,
+    assets: &[Asset],
+) -> Result<(), ContractError> {
+    let Some(limit) = RATE_LIMIT.may_load(deps.storage)? else {
+        return Ok(());
+    };
+    if RATE_LIMIT_EXEMPT.has(deps.storage, sender) {
+        return Ok(());
+    }
+    let height = env.block.height;
+    let key = activity_key(sender, token);
+    let mut activity = window_activity(deps.as_ref(), &limit, sender, token, height)?;
+
+    let mut retry_at_height = None;
+    if let Some(max_burns) = limit.max_burns {
+        let incoming = assets.len() as u32;
+        if incoming > max_burns {
+            return Err(StdError::generic_err(format!(
+                "{} burns in one message exceed the limit of {} per {} blocks",
+                incoming, max_burns, limit.window_blocks
+            ))
+            .into());
+        }
+        let burns: u32 = activity.iter().map(|block| block.burns).sum();
+        if burns + incoming > max_burns {
+            let excess = Uint128::from(burns + incoming - max_burns);
+            let retry = retry_height(&activity, limit.window_blocks, excess, |block| block.burns.into());
+            retry_at_height = retry_at_height.max(Some(retry));
+        }
+    }
+
+    let mut limited = vec![];
+    for max in limit.max_amounts.iter() {
+        let incoming: Uint128 = assets
+            .iter()
+            .filter(|asset| asset.info.equal(&max.info))
+            .map(|asset| asset.amount)
+            .sum();
+        if incoming.is_zero() {
+            continue;
+        }
+        if incoming > max.amount {
+            return Err(StdError::generic_err(format!(
+                "Burning {}{} exceeds the limit of {} per {} blocks",
+                incoming, max.info, max, limit.window_blocks
+            ))
+            .into());
+        }
+        let total: Uint128 = activity.iter().map(|block| block_amount(block, &max.info)).sum();
+        if total + incoming > max.amount {
+            let excess = total + incoming - max.amount;
+            let retry = retry_height(&activity, limit.window_blocks, excess, |block| block_amount(block, &max.info));
+            retry_at_height = retry_at_height.max(Some(retry));
+        }
+        limited.push(Asset {
+            info: max.info.clone(),
+            amount: incoming,
+        });
+    }
+
+    if let Some(retry_at_height) = retry_at_height {
+        return Err(ContractError::RateLimited {
+            sender: sender.to_string(),
+            retry_at_height,
+        });
+    }
+
+    // Burns of the same block share one entry, only limited assets are tracked
+    let mut current = match activity.pop() {
+        Some(block) if block.height == height => block,
+        last => {
+            activity.extend(last);
+            BlockActivity {
+                height,
+                burns: 0,
+                amounts: vec![],
+            }
+        }
+    };
+    // Burn counts only matter with a `max_burns`, blocks that count towards no limit are not kept
+    if limit.max_burns.is_some() {
+        current.burns += assets.len() as u32;
+    }
+    for asset in limited {
+        match current.amounts.iter_mut().find(|amount| amount.info.equal(&asset.info)) {
+            Some(amount) => amount.amount += asset.amount,
+            None => current.amounts.push(asset),
+        }
+    }
+    if current.burns > 0 || !current.amounts.is_empty() {
+        activity.push(current);
+    }
+    if activity.is_empty() {
+        SENDER_ACTIVITY.remove(deps.storage, key);
+    } else {
+        SENDER_ACTIVITY.save(deps.storage, key, &activity)?;
+    }
+
+    Ok(())
+}
+
+pub fn query_rate_limit(deps: Deps) -> StdResult<RateLimitResponse> {
+    Ok(RateLimitResponse {
+        limit: RATE_LIMIT.may_load(deps.storage)?,
+    })
+}
+
+/// What `sender` burned within the current window, measured against the configured limits. With a
+/// `token` only the CW20 burns that token relayed for the sender are counted.
+pub fn query_sender_rate_limit(
+    deps: Deps,
+    env: Env,
+    sender: String,
+    token: Option<String>,
+) -> StdResult<SenderRateLimitResponse> {
+    let sender = deps.api.addr_validate(&sender)?;
+    let token = token.map(|token| deps.api.addr_validate(&token)).transpose()?;
+    let exempt = RATE_LIMIT_EXEMPT.has(deps.storage, &sender);
+    let activity = match RATE_LIMIT.may_load(deps.storage)? {
+        Some(limit) if !exempt => window_activity(deps, &limit, &sender, token.as_ref(), env.block.height)?,
+        _ => vec![],
+    };
+
+    let mut amounts: Vec<Asset> = vec![];
+    for asset in activity.iter().flat_map(|block| block.amounts.iter()) {
+        match amounts.iter_mut().find(|amount| amount.info.equal(&asset.info)) {
+            Some(amount) => amount.amount += asset.amount,
+            None => amounts.push(asset.clone()),
+        }
+    }
+
+    Ok(SenderRateLimitResponse {
+        exempt,
+        burns: activity.iter().map(|block| block.burns).sum(),
+        amounts,
+    })
+}
+
+pub fn query_rate_limit_exemptions(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|address| deps.api.addr_validate(&address)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let exemptions = RATE_LIMIT_EXEMPT
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&exemptions)
+}
@@ -0,0 +1,133 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult,
+};
+use cw_storage_plus::Bound;
+use injective_cosmwasm::InjectiveMsgWrapper;
+
+use crate::contract::assert_admin;
+use crate::msg::ReferralCutResponse;
+use crate::state::{Asset, ReferralStats, REFERRAL_CUT_BPS, REFERRAL_STATS};
+
+/// Highest referral cut the admin can configure, 10% of the burn
+pub const MAX_REFERRAL_CUT_BPS: u16 = 1_000;
+const BPS_DENOMINATOR: u128 = 10_000;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+pub fn set_referral_cut(deps: DepsMut, info: MessageInfo, bps: u16) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    if bps > MAX_REFERRAL_CUT_BPS {
+        return Err(StdError::generic_err(format!(
+            "Referral cut cannot exceed {} basis points",
+            MAX_REFERRAL_CUT_BPS
+        )));
+    }
+    REFERRAL_CUT_BPS.save(deps.storage, &bps)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_referral_cut")
+        .add_attribute("bps", bps.to_string()))
+}
+
+/// Splits the referrer's cut off a burn and adds the burn to the referrer's totals. Returns what
+/// is left to burn, and the payout of the cut unless it rounds down to nothing.
+pub fn apply_referral(
+    deps: DepsMut,
+    env: &Env,
+    sender: &Addr,
+    referrer: &str,
+    asset: Asset,
+) -> StdResult<(Asset, Option<CosmosMsg<InjectiveMsgWrapper>>)> {
+    let referrer = deps.api.addr_validate(referrer)?;
+    if referrer == *sender {
+        return Err(StdError::generic_err("Burners cannot refer themselves"));
+    }
+    if referrer == env.contract.address {
+        return Err(StdError::generic_err("Referrer cannot be this contract"));
+    }
+
+    let bps = REFERRAL_CUT_BPS.may_load(deps.storage)?.unwrap_or_default();
+    let cut = asset.amount.multiply_ratio(bps as u128, BPS_DENOMINATOR);
+    let net = Asset {
+        info: asset.info.clone(),
+        amount: asset.amount - cut,
+    };
+
+    let key = asset.info.to_string();
+    let mut stats = REFERRAL_STATS
+        .may_load(deps.storage, (&referrer, &key))?
+        .unwrap_or_else(|| ReferralStats {
+            referrer: referrer.clone(),
+            asset_info: asset.info.clone(),
+            burns: 0,
+            burned: Default::default(),
+            paid: Default::default(),
+        });
+    stats.burns += 1;
+    stats.burned += net.amount;
+    stats.paid += cut;
+    REFERRAL_STATS.save(deps.storage, (&referrer, &key), &stats)?;
+
+    if cut.is_zero() {
+        return Ok((net, None));
+    }
+    let payout = Asset {
+        info: asset.info,
+        amount: cut,
+    }
+    .into_msg(referrer)?
+    .change_custom()
+    .ok_or_else(|| StdError::generic_err("Unexpected custom referral payout message"))?;
+    Ok((net, Some(payout)))
+}
+
+pub fn query_referral_cut(deps: Deps) -> StdResult<ReferralCutResponse> {
+    Ok(ReferralCutResponse {
+        bps: REFERRAL_CUT_BPS.may_load(deps.storage)?.unwrap_or_default(),
+        max_bps: MAX_REFERRAL_CUT_BPS,
+    })
+}
+
+/// Totals of `referrer` per asset, ordered by asset
+pub fn query_referrer_stats(
+    deps: Deps,
+    referrer: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let referrer = deps.api.addr_validate(&referrer)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let stats = REFERRAL_STATS
+        .prefix(&referrer)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, stats)| stats))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&stats)
+}
+
+/// Totals of every referrer, ordered by referrer then asset. `start_after` is a referrer and asset pair.
+pub fn query_referral_stats(
+    deps: Deps,
+    start_after: Option<(String, String)>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|(referrer, asset)| deps.api.addr_validate(&referrer).map(|referrer| (referrer, asset)))
+        .transpose()?;
+    let start = start_after
+        .as_ref()
+        .map(|(referrer, asset)| Bound::exclusive((referrer, asset.as_str())));
+
+    let stats = REFERRAL_STATS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, stats)| stats))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&stats)
+}
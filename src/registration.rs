@@ -0,0 +1,211 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError,
+    StdResult, SubMsg, Uint128, WasmMsg,
+};
+use cw_storage_plus::Bound;
+use injective_cosmwasm::InjectiveMsgWrapper;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::assert_admin;
+use crate::state::{
+    load_config, Cw20Registration, ADAPTER_REGISTERED, CW20_REGISTRATIONS, REGISTRATION_ALLOWLIST, REGISTRATION_BUDGET,
+};
+
+/// Denom the adapter charges its registration fee in
+pub const REGISTRATION_DENOM: &str = "inj";
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdapterRegisterMsg {
+    RegisterCw20Contract { addr: Addr },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdapterQueryMsg {
+    RegisteredContracts {},
+    NewDenomFee {},
+}
+
+pub fn load_registration_budget(deps: Deps) -> StdResult<Uint128> {
+    Ok(REGISTRATION_BUDGET.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub fn fund_registration_budget(deps: DepsMut, info: MessageInfo) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    if info.funds.len() != 1 || info.funds[0].denom != REGISTRATION_DENOM || info.funds[0].amount.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "Registration budget must be funded with {} only",
+            REGISTRATION_DENOM
+        )));
+    }
+
+    let budget = load_registration_budget(deps.as_ref())? + info.funds[0].amount;
+    REGISTRATION_BUDGET.save(deps.storage, &budget)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "fund_registration_budget")
+        .add_attribute("amount", info.funds[0].amount.to_string())
+        .add_attribute("budget", budget.to_string()))
+}
+
+pub fn withdraw_registration_budget(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Uint128,
+    recipient: Option<String>,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender,
+    };
+    let budget = load_registration_budget(deps.as_ref())?;
+    let budget = budget
+        .checked_sub(amount)
+        .map_err(|_| StdError::generic_err(format!("Registration budget is only {}", budget)))?;
+    REGISTRATION_BUDGET.save(deps.storage, &budget)?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin::new(amount, REGISTRATION_DENOM)],
+        })
+        .add_attribute("action", "withdraw_registration_budget")
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("budget", budget.to_string()))
+}
+
+pub fn set_registration_allowed(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw20: String,
+    allowed: bool,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let cw20 = deps.api.addr_validate(&cw20)?;
+    if allowed {
+        REGISTRATION_ALLOWLIST.save(deps.storage, &cw20, &Empty {})?;
+    } else {
+        REGISTRATION_ALLOWLIST.remove(deps.storage, &cw20);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_registration_allowed")
+        .add_attribute("cw20", cw20)
+        .add_attribute("allowed", allowed.to_string()))
+}
+
+/// Message registering a CW20 with the adapter and the fee it costs, `None` when it is
+/// already registered. Fails when the token is not allowlisted or the budget cannot cover the fee.
+pub fn registration_message(deps: Deps, cw20: &Addr) -> StdResult<Option<(SubMsg<InjectiveMsgWrapper>, Uint128)>> {
+    // Registrations paid earlier in the same transaction do not show up at the adapter yet
+    if CW20_REGISTRATIONS.has(deps.storage, cw20.as_str()) || ADAPTER_REGISTERED.has(deps.storage, cw20) {
+        return Ok(None);
+    }
+
+    let adapter = load_config(deps)?.adapter_contract;
+
+    let registered: Vec<Addr> = deps
+        .querier
+        .query_wasm_smart(&adapter, &AdapterQueryMsg::RegisteredContracts {})?;
+    if registered.contains(cw20) {
+        return Ok(None);
+    }
+    // Anyone can deploy a CW20 and send it here, only tokens the admin approved are paid for
+    if !REGISTRATION_ALLOWLIST.has(deps.storage, cw20) {
+        return Err(StdError::generic_err(format!(
+            "{} is not registered with the adapter and not allowlisted for registration",
+            cw20
+        )));
+    }
+
+    let fee: Uint128 = deps.querier.query_wasm_smart(&adapter, &AdapterQueryMsg::NewDenomFee {})?;
+    let budget = load_registration_budget(deps)?;
+    if budget < fee {
+        return Err(StdError::generic_err(format!(
+            "{} is not registered with the adapter and the registration budget ({}{}) cannot cover the fee ({}{})",
+            cw20, budget, REGISTRATION_DENOM, fee, REGISTRATION_DENOM
+        )));
+    }
+
+    let register_msg = WasmMsg::Execute {
+        contract_addr: adapter,
+        msg: to_json_binary(&AdapterRegisterMsg::RegisterCw20Contract { addr: cw20.clone() })?,
+        funds: if fee.is_zero() {
+            vec![]
+        } else {
+            vec![Coin::new(fee, REGISTRATION_DENOM)]
+        },
+    };
+
+    Ok(Some((SubMsg::new(register_msg), fee)))
+}
+
+/// Registers an unknown CW20 with the adapter before it is sent there, paying from the budget
+pub fn register_cw20_if_needed(
+    deps: DepsMut,
+    env: &Env,
+    cw20: &Addr,
+    triggered_by: &Addr,
+    messages: &mut Vec<SubMsg<InjectiveMsgWrapper>>,
+) -> StdResult<()> {
+    let (register_msg, fee) = match registration_message(deps.as_ref(), cw20)? {
+        Some(registration) => registration,
+        None => {
+            // The adapter's registry is not paginated, only look a token up there once
+            if !CW20_REGISTRATIONS.has(deps.storage, cw20.as_str()) {
+                ADAPTER_REGISTERED.save(deps.storage, cw20, &Empty {})?;
+            }
+            return Ok(());
+        }
+    };
+
+    let budget = load_registration_budget(deps.as_ref())? - fee;
+    REGISTRATION_BUDGET.save(deps.storage, &budget)?;
+    CW20_REGISTRATIONS.save(
+        deps.storage,
+        cw20.as_str(),
+        &Cw20Registration {
+            cw20: cw20.clone(),
+            fee,
+            triggered_by: triggered_by.clone(),
+            registered_at: env.block.time,
+            height: env.block.height,
+        },
+    )?;
+    messages.push(register_msg);
+
+    Ok(())
+}
+
+pub fn query_registrations(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let registrations = CW20_REGISTRATIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, registration)| registration))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&registrations)
+}
+
+pub fn query_registration_allowlist(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let allowlist = REGISTRATION_ALLOWLIST
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&allowlist)
+}
@@ -0,0 +1,184 @@
+use cosmwasm_std::{
+    to_json_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError,
+    StdResult, WasmMsg,
+};
+use cw_storage_plus::Bound;
+use injective_cosmwasm::InjectiveMsgWrapper;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::assert_admin;
+use crate::escrow::assert_unreserved;
+use crate::state::{
+    RescueItem, RescueRecord, RescueStatus, DEFAULT_RESCUE_TIMELOCK, MIN_RESCUE_TIMELOCK, RESCUES, RESCUE_COUNT,
+    RESCUE_TIMELOCK,
+};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721ExecuteMsg {
+    TransferNft { recipient: String, token_id: String },
+}
+
+pub fn load_rescue_timelock(deps: Deps) -> StdResult<u64> {
+    Ok(RESCUE_TIMELOCK
+        .may_load(deps.storage)?
+        .unwrap_or(DEFAULT_RESCUE_TIMELOCK))
+}
+
+pub fn set_rescue_timelock(
+    deps: DepsMut,
+    info: MessageInfo,
+    seconds: u64,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    // A short timelock would let the admin propose and execute a rescue before anyone can react
+    if seconds < MIN_RESCUE_TIMELOCK {
+        return Err(StdError::generic_err(format!(
+            "Rescue timelock must be at least {} seconds",
+            MIN_RESCUE_TIMELOCK
+        )));
+    }
+    let previous = load_rescue_timelock(deps.as_ref())?;
+    RESCUE_TIMELOCK.save(deps.storage, &seconds)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_rescue_timelock")
+        .add_attribute("previous_seconds", previous.to_string())
+        .add_attribute("seconds", seconds.to_string()))
+}
+
+pub fn propose_rescue(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    item: RescueItem,
+    recipient: String,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+    match &item {
+        RescueItem::Asset { asset } => {
+            if asset.amount.is_zero() {
+                return Err(StdError::generic_err("Rescue amount must be greater than zero"));
+            }
+            assert_unreserved(deps.as_ref(), &env, asset)?;
+        }
+        RescueItem::Cw721 { contract_addr, .. } => {
+            deps.api.addr_validate(contract_addr)?;
+        }
+    }
+
+    let id = RESCUE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    RESCUE_COUNT.save(deps.storage, &id)?;
+
+    let timelock = load_rescue_timelock(deps.as_ref())?;
+    let record = RescueRecord {
+        id,
+        item,
+        recipient: recipient.to_string(),
+        proposed_by: info.sender.to_string(),
+        proposed_at: env.block.time,
+        executable_at: env.block.time.plus_seconds(timelock),
+        status: RescueStatus::Pending,
+        closed_at: None,
+    };
+    RESCUES.save(deps.storage, id, &record)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_rescue")
+        .add_attribute("rescue_id", id.to_string())
+        .add_attribute("recipient", record.recipient)
+        .add_attribute("executable_at", record.executable_at.seconds().to_string()))
+}
+
+pub fn execute_rescue(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let mut record = load_pending_rescue(deps.as_ref(), id)?;
+    if env.block.time < record.executable_at {
+        return Err(StdError::generic_err(format!(
+            "Rescue {} is timelocked until {}",
+            id,
+            record.executable_at.seconds()
+        )));
+    }
+
+    // Escrows may have been created since the proposal, they still take precedence
+    if let RescueItem::Asset { asset } = &record.item {
+        assert_unreserved(deps.as_ref(), &env, asset)?;
+    }
+    let recipient = deps.api.addr_validate(&record.recipient)?;
+    let message: CosmosMsg<InjectiveMsgWrapper> = match record.item.clone() {
+        RescueItem::Asset { asset } => asset
+            .into_msg(recipient)?
+            .change_custom()
+            .ok_or_else(|| StdError::generic_err("Unexpected custom rescue message"))?,
+        RescueItem::Cw721 { contract_addr, token_id } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: recipient.to_string(),
+                token_id,
+            })?,
+            funds: vec![],
+        }),
+    };
+
+    record.status = RescueStatus::Executed;
+    record.closed_at = Some(env.block.time);
+    RESCUES.save(deps.storage, id, &record)?;
+
+    Ok(Response::new()
+        .add_message(message)
+        .add_attribute("action", "execute_rescue")
+        .add_attribute("rescue_id", id.to_string())
+        .add_attribute("recipient", record.recipient))
+}
+
+pub fn cancel_rescue(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    let mut record = load_pending_rescue(deps.as_ref(), id)?;
+    record.status = RescueStatus::Cancelled;
+    record.closed_at = Some(env.block.time);
+    RESCUES.save(deps.storage, id, &record)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_rescue")
+        .add_attribute("rescue_id", id.to_string()))
+}
+
+fn load_pending_rescue(deps: Deps, id: u64) -> StdResult<RescueRecord> {
+    let record = RESCUES.load(deps.storage, id)?;
+    if record.status != RescueStatus::Pending {
+        return Err(StdError::generic_err(format!("Rescue {} is no longer pending", id)));
+    }
+    Ok(record)
+}
+
+pub fn query_rescues(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let rescues = RESCUES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, record)| record))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&rescues)
+}
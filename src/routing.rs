@@ -0,0 +1,59 @@
+use cosmwasm_std::{Addr, Deps, DepsMut, MessageInfo, Response, StdError, StdResult};
+use injective_cosmwasm::exchange::subaccount::checked_address_to_subaccount_id;
+use injective_cosmwasm::exchange::types::SubaccountId;
+use injective_cosmwasm::InjectiveMsgWrapper;
+
+use crate::contract::assert_admin;
+use crate::msg::SubaccountRoutingResponse;
+use crate::state::{SubaccountRouting, SUBACCOUNT_ROUTING};
+
+/// Nonce of the intermediate subaccount burns used before routing became configurable
+pub const DEFAULT_SUBACCOUNT_NONCE: u32 = 1;
+
+pub fn load_subaccount_routing(deps: Deps) -> StdResult<SubaccountRouting> {
+    Ok(SUBACCOUNT_ROUTING
+        .may_load(deps.storage)?
+        .unwrap_or(SubaccountRouting::Intermediate {
+            nonce: DEFAULT_SUBACCOUNT_NONCE,
+        }))
+}
+
+/// Subaccount of this contract burns are routed through
+pub fn contract_subaccount_id(deps: Deps, contract_address: &Addr) -> StdResult<SubaccountId> {
+    Ok(checked_address_to_subaccount_id(
+        contract_address,
+        load_subaccount_routing(deps)?.nonce(),
+    ))
+}
+
+pub fn set_subaccount_routing(
+    deps: DepsMut,
+    info: MessageInfo,
+    routing: SubaccountRouting,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_admin(deps.as_ref(), &info)?;
+
+    // Nonce 0 is the default subaccount, backed by the bank balance, which is what direct mode is for
+    if routing == (SubaccountRouting::Intermediate { nonce: 0 }) {
+        return Err(StdError::generic_err(
+            "Intermediate subaccount nonce must be greater than zero, use direct routing for the default subaccount",
+        ));
+    }
+
+    SUBACCOUNT_ROUTING.save(deps.storage, &routing)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_subaccount_routing")
+        .add_attribute("routing", routing.to_string()))
+}
+
+pub fn query_subaccount_routing(deps: Deps, contract_address: &Addr) -> StdResult<SubaccountRoutingResponse> {
+    let routing = load_subaccount_routing(deps)?;
+
+    Ok(SubaccountRoutingResponse {
+        subaccount_id: checked_address_to_subaccount_id(contract_address, routing.nonce())
+            .as_str()
+            .to_string(),
+        routing,
+    })
+}
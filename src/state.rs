@@ -1,14 +1,95 @@
 use schemars::JsonSchema;
-use cosmwasm_std::{Deps, DepsMut, StdError, StdResult,
-    CanonicalAddr, Uint128, Addr, CosmosMsg, WasmMsg, BankMsg, Coin, SubMsg, MessageInfo, Api, to_json_binary, QuerierWrapper};
+use cosmwasm_std::{Deps, DepsMut, StdError, StdResult, Timestamp,
+    BlockInfo, CanonicalAddr, Decimal, Empty, Uint128, Addr, CosmosMsg, WasmMsg, BankMsg, Coin, SubMsg, MessageInfo, Api, to_json_binary, QuerierWrapper};
 use cw20::{Cw20ExecuteMsg};
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use crate::querier::{query_balance, query_token_balance};
-use cw_storage_plus::Item;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use injective_cosmwasm::exchange::types::MarketId;
+use injective_cosmwasm::oracle::types::OracleType;
+use injective_math::FPDecimal;
 
 pub const CONFIG: Item<Config> = Item::new("config");
+pub const SWAP_ROUTES: Map<&str, SwapRoute> = Map::new("swap_routes");
+pub const TRACKED_CW20S: Item<Vec<String>> = Item::new("tracked_cw20s");
+pub const RESCUE_TIMELOCK: Item<u64> = Item::new("rescue_timelock");
+pub const RESCUE_COUNT: Item<u64> = Item::new("rescue_count");
+pub const RESCUES: Map<u64, RescueRecord> = Map::new("rescues");
+
+pub const BURN_COUNT: Item<u64> = Item::new("burn_count");
+pub const HOOKS: Item<Vec<String>> = Item::new("hooks");
+pub const HOOK_AUTO_REMOVE: Item<bool> = Item::new("hook_auto_remove");
+/// Soulbound cw721 collection minting burn certificates, this contract is its minter
+pub const CERTIFICATE_CONTRACT: Item<String> = Item::new("certificate_contract");
+/// Amount a burn has to exceed to earn a certificate, keyed by asset
+pub const CERTIFICATE_THRESHOLDS: Map<&str, Uint128> = Map::new("certificate_thresholds");
+/// Non-transferable cw20 minted to burners, this contract is its minter
+pub const POINTS_TOKEN: Item<String> = Item::new("points_token");
+/// Points minted per base unit burned, keyed by asset
+pub const POINTS_RATES: Map<&str, Decimal> = Map::new("points_rates");
+/// INJ set aside by the admin to pay adapter registration fees of unknown CW20s
+pub const REGISTRATION_BUDGET: Item<Uint128> = Item::new("registration_budget");
+pub const CW20_REGISTRATIONS: Map<&str, Cw20Registration> = Map::new("cw20_registrations");
+/// CW20s the registration budget may pay the adapter fee for
+pub const REGISTRATION_ALLOWLIST: Map<&Addr, Empty> = Map::new("registration_allowlist");
+/// CW20s found in the adapter's registry, later burns of them skip querying the whole registry
+pub const ADAPTER_REGISTERED: Map<&Addr, Empty> = Map::new("adapter_registered");
+/// How exchange messages are encoded, custom bindings unless the admin switches
+pub const MESSAGE_BACKEND: Item<MessageBackend> = Item::new("message_backend");
+/// Which subaccount of this contract burns go through, nonce 1 unless the admin changes it
+pub const SUBACCOUNT_ROUTING: Item<SubaccountRouting> = Item::new("subaccount_routing");
+pub const PLEDGE_COUNT: Item<u64> = Item::new("pledge_count");
+/// Pending pledges by unlock point (see `BlockPoint::index_key`) then id, closed ones are removed
+pub const PLEDGES_DUE: Map<(u8, u64, u64), Empty> = Map::new("pledges_due");
+pub const CONDITIONAL_BURN_COUNT: Item<u64> = Item::new("conditional_burn_count");
+pub const CONDITIONAL_BURNS: Map<u64, ConditionalBurn> = Map::new("conditional_burns");
+/// Ids of conditional burns still open, triggered and refunded ones are removed
+pub const OPEN_CONDITIONAL_BURNS: Map<u64, Empty> = Map::new("open_conditional_burns");
+pub const STREAM_COUNT: Item<u64> = Item::new("stream_count");
+pub const STREAMS: Map<u64, BurnStream> = Map::new("streams");
+pub const CAMPAIGN_COUNT: Item<u64> = Item::new("campaign_count");
+/// Amount each user had matched so far, keyed by campaign id and user
+pub const CAMPAIGN_MATCHES: Map<(u64, &Addr), Uint128> = Map::new("campaign_matches");
+/// Campaigns that may still match burns, by asset then id. Kept apart from the `campaigns` asset
+/// index so matching never walks closed campaigns.
+pub const RUNNING_CAMPAIGNS: Map<(&str, u64), Empty> = Map::new("running_campaigns");
+/// Smallest pool a campaign on an asset may start with, campaigns are off for assets without one
+pub const CAMPAIGN_MIN_POOLS: Map<&str, Uint128> = Map::new("campaign_min_pools");
+/// Share of a referred burn paid to the referrer, in basis points, nothing unless configured
+pub const REFERRAL_CUT_BPS: Item<u16> = Item::new("referral_cut_bps");
+/// Totals of burns each referrer brought in, keyed by referrer and asset
+pub const REFERRAL_STATS: Map<(&Addr, &str), ReferralStats> = Map::new("referral_stats");
+/// Whether anyone may start a drive, only the admin unless switched on
+pub const DRIVE_CREATION_OPEN: Item<bool> = Item::new("drive_creation_open");
+pub const DRIVE_COUNT: Item<u64> = Item::new("drive_count");
+pub const DRIVES: Map<u64, Drive> = Map::new("drives");
+/// Which senders may burn, everyone unless the admin switches to a list
+pub const SENDER_ACCESS: Item<SenderAccess> = Item::new("sender_access");
+pub const SENDER_ALLOWLIST: Map<&Addr, Empty> = Map::new("sender_allowlist");
+pub const SENDER_BLOCKLIST: Map<&Addr, Empty> = Map::new("sender_blocklist");
+/// Per-sender burn limits, no limits unless configured
+pub const RATE_LIMIT: Item<RateLimit> = Item::new("rate_limit");
+/// Senders the admin approved for high volumes, never rate limited
+pub const RATE_LIMIT_EXEMPT: Map<&Addr, Empty> = Map::new("rate_limit_exempt");
+/// Burns of each sender within the current window, one entry per block, oldest first. Keyed by
+/// sender then the CW20 contract that relayed the burn, empty for burns the sender signed itself.
+pub const SENDER_ACTIVITY: Map<(&Addr, &str), Vec<BlockActivity>> = Map::new("sender_activity");
+/// Idempotency keys consumed by burn requests, keyed by sender and key
+pub const IDEMPOTENCY_KEYS: Map<(&Addr, &str), ConsumedKey> = Map::new("idempotency_keys");
+/// Nonce the next burn permit of each signer must carry
+pub const PERMIT_NONCES: Map<&Addr, u64> = Map::new("permit_nonces");
+/// Total of each asset held for open pledges, conditional burns, streams and campaign pools,
+/// keyed by asset
+pub const ESCROWED: Map<&str, Uint128> = Map::new("escrowed");
+/// Set while a burn callback runs so the callee cannot re-enter the contract
+pub const CALLBACK_LOCK: Item<bool> = Item::new("callback_lock");
+
+/// Delay before a proposed rescue can be executed unless the admin configures another one
+pub const DEFAULT_RESCUE_TIMELOCK: u64 = 3 * 24 * 60 * 60;
+/// Shortest rescue timelock the admin can configure
+pub const MIN_RESCUE_TIMELOCK: u64 = 24 * 60 * 60;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Config {
@@ -17,6 +98,557 @@ pub struct Config {
     pub burn_auction_subaccount: String,
 }
 
+/// What a single burn did, handed to everything that reacts to burns
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BurnDetails {
+    pub sender: Addr,
+    pub beneficiary: Addr,
+    pub asset: Asset,
+    /// Denom that reaches the burn auction, differs from the asset for CW20s and swapped denoms
+    pub auction_denom: String,
+}
+
+/// Encoding of the Deposit, Withdraw and ExternalTransfer exchange messages. Spot market
+/// orders always go through the custom bindings.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageBackend {
+    /// `CosmosMsg::Custom` with `injective-cosmwasm` bindings
+    #[default]
+    Custom,
+    /// `CosmosMsg::Any` carrying the `injective-std` protobuf messages
+    Protobuf,
+}
+
+impl fmt::Display for MessageBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageBackend::Custom => write!(f, "custom"),
+            MessageBackend::Protobuf => write!(f, "protobuf"),
+        }
+    }
+}
+
+/// Burn of a CW20 signed off-chain by its holder, submitted by anyone on the holder's behalf.
+/// The tokens are pulled with `TransferFrom`, so the holder must have granted this contract an allowance.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BurnPermit {
+    pub token: String,
+    pub amount: Uint128,
+    pub nonce: u64,
+    pub expiry: BlockPoint,
+    pub chain_id: String,
+    pub contract_addr: String,
+}
+
+/// Block a request consumed its idempotency key in
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConsumedKey {
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+/// Sliding window limits applied to every sender that is not exempt
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RateLimit {
+    /// Length of the window in blocks, a burn counts until this many blocks have passed
+    pub window_blocks: u64,
+    /// Most burns within the window, every asset of a batch counts as one
+    pub max_burns: Option<u32>,
+    /// Most of each listed asset that can be burned within the window
+    pub max_amounts: Vec<Asset>,
+}
+
+/// What a sender burned in one block
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BlockActivity {
+    pub height: u64,
+    pub burns: u32,
+    pub amounts: Vec<Asset>,
+}
+
+/// Who may burn through this contract. Both lists are kept when switching, only the one of
+/// the active mode is enforced.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SenderAccess {
+    #[default]
+    Open,
+    /// Only senders on the allowlist
+    Allowlist,
+    /// Everyone but senders on the blocklist
+    Blocklist,
+}
+
+impl fmt::Display for SenderAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SenderAccess::Open => write!(f, "open"),
+            SenderAccess::Allowlist => write!(f, "allowlist"),
+            SenderAccess::Blocklist => write!(f, "blocklist"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SenderList {
+    Allowlist,
+    Blocklist,
+}
+
+impl fmt::Display for SenderList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SenderList::Allowlist => write!(f, "allowlist"),
+            SenderList::Blocklist => write!(f, "blocklist"),
+        }
+    }
+}
+
+impl SenderList {
+    pub fn storage<'a>(&self) -> Map<&'a Addr, Empty> {
+        match self {
+            SenderList::Allowlist => SENDER_ALLOWLIST,
+            SenderList::Blocklist => SENDER_BLOCKLIST,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubaccountRouting {
+    /// Deposit into the subaccount with this nonce, then transfer to the burn auction
+    Intermediate { nonce: u32 },
+    /// Transfer straight out of the default subaccount, which spends the bank balance
+    /// and saves the deposit message
+    Direct {},
+}
+
+impl SubaccountRouting {
+    pub fn nonce(&self) -> u32 {
+        match self {
+            SubaccountRouting::Intermediate { nonce } => *nonce,
+            SubaccountRouting::Direct {} => 0,
+        }
+    }
+
+    pub fn is_direct(&self) -> bool {
+        matches!(self, SubaccountRouting::Direct {})
+    }
+}
+
+impl fmt::Display for SubaccountRouting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubaccountRouting::Intermediate { nonce } => write!(f, "intermediate:{}", nonce),
+            SubaccountRouting::Direct {} => write!(f, "direct"),
+        }
+    }
+}
+
+/// Spot market used to sell a denom before its proceeds are sent to the burn auction.
+/// The configured denom must be the base asset of the market, proceeds are paid in `quote_denom`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SwapRoute {
+    pub market_id: MarketId,
+    pub quote_denom: String,
+    /// Lowest price the atomic sell order is allowed to fill at
+    pub worst_price: FPDecimal,
+    pub min_quantity_tick_size: FPDecimal,
+}
+
+/// Carried as the payload of the market order submessage so the reply knows what to burn
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingSwap {
+    pub input_denom: String,
+    pub quote_denom: String,
+    /// Input the order was placed for, whatever it does not fill is burned as is
+    pub quantity: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RescueItem {
+    Asset { asset: Asset },
+    Cw721 { contract_addr: String, token_id: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RescueStatus {
+    Pending,
+    Executed,
+    Cancelled,
+}
+
+/// Entry of the on-chain rescue log, kept forever so rescues can be audited
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RescueRecord {
+    pub id: u64,
+    pub item: RescueItem,
+    pub recipient: String,
+    pub proposed_by: String,
+    pub proposed_at: Timestamp,
+    pub executable_at: Timestamp,
+    pub status: RescueStatus,
+    pub closed_at: Option<Timestamp>,
+}
+
+/// Block height or time, used for pledge unlocks, request deadlines and permit expiries
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockPoint {
+    AtHeight { height: u64 },
+    AtTime { time: Timestamp },
+}
+
+impl BlockPoint {
+    pub fn is_reached(&self, block: &BlockInfo) -> bool {
+        match self {
+            BlockPoint::AtHeight { height } => block.height >= *height,
+            BlockPoint::AtTime { time } => block.time >= *time,
+        }
+    }
+
+    /// Whether the block is past this point, a deadline still accepts requests at the point itself
+    pub fn is_passed(&self, block: &BlockInfo) -> bool {
+        match self {
+            BlockPoint::AtHeight { height } => block.height > *height,
+            BlockPoint::AtTime { time } => block.time > *time,
+        }
+    }
+
+    /// Index key ordering height based points before time based ones
+    pub fn index_key(&self) -> (u8, u64) {
+        match self {
+            BlockPoint::AtHeight { height } => (0, *height),
+            BlockPoint::AtTime { time } => (1, time.nanos()),
+        }
+    }
+}
+
+impl fmt::Display for BlockPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockPoint::AtHeight { height } => write!(f, "height:{}", height),
+            BlockPoint::AtTime { time } => write!(f, "time:{}", time.seconds()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PledgeTerms {
+    /// Anyone may burn the pledge once this point is reached
+    pub unlock: BlockPoint,
+    /// The pledger may cancel until this point, `None` locks the pledge in right away
+    pub cancellable_until: Option<BlockPoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PledgeStatus {
+    Pending,
+    Burned,
+    Cancelled,
+}
+
+/// Asset escrowed now to be burned later, kept after closing as a public record
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Pledge {
+    pub id: u64,
+    pub pledger: Addr,
+    pub asset: Asset,
+    pub terms: PledgeTerms,
+    pub created_at: Timestamp,
+    pub status: PledgeStatus,
+    pub closed_at: Option<Timestamp>,
+}
+
+pub struct PledgeIndexes<'a> {
+    pub pledger: MultiIndex<'a, Addr, Pledge, u64>,
+}
+
+impl IndexList<Pledge> for PledgeIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Pledge>> + '_> {
+        let v: Vec<&dyn Index<Pledge>> = vec![&self.pledger];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn pledges<'a>() -> IndexedMap<u64, Pledge, PledgeIndexes<'a>> {
+    let indexes = PledgeIndexes {
+        pledger: MultiIndex::new(|_, pledge| pledge.pledger.clone(), "pledges", "pledges__pledger"),
+    };
+    IndexedMap::new("pledges", indexes)
+}
+
+/// Injective oracle price pair a conditional burn watches
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct OracleSource {
+    pub oracle_type: OracleType,
+    pub base: String,
+    pub quote: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceComparator {
+    /// Price strictly above the threshold
+    Above,
+    /// Price strictly below the threshold
+    Below,
+}
+
+impl PriceComparator {
+    pub fn holds(&self, price: FPDecimal, threshold: FPDecimal) -> bool {
+        match self {
+            PriceComparator::Above => price > threshold,
+            PriceComparator::Below => price < threshold,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BurnCondition {
+    pub source: OracleSource,
+    pub comparator: PriceComparator,
+    pub threshold: FPDecimal,
+    /// The order can no longer trigger from this time on and its escrow can be refunded
+    pub expires_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionalBurnStatus {
+    Open,
+    Burned,
+    Refunded,
+}
+
+/// Asset escrowed until an oracle price condition holds, kept after closing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConditionalBurn {
+    pub id: u64,
+    pub owner: Addr,
+    pub asset: Asset,
+    pub condition: BurnCondition,
+    pub created_at: Timestamp,
+    pub status: ConditionalBurnStatus,
+    pub closed_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StreamSchedule {
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    /// Seconds between releases, vesting steps up once per interval
+    pub interval: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamStatus {
+    Active,
+    Completed,
+    Cancelled,
+}
+
+/// Asset escrowed to be burned linearly over a schedule, kept after closing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BurnStream {
+    pub id: u64,
+    pub owner: Addr,
+    pub asset: Asset,
+    pub schedule: StreamSchedule,
+    /// Burned so far
+    pub released: Uint128,
+    /// Sent back to the owner on cancellation
+    pub refunded: Uint128,
+    pub created_at: Timestamp,
+    pub status: StreamStatus,
+    pub closed_at: Option<Timestamp>,
+}
+
+impl BurnStream {
+    /// Amount vested at `time`, counted in whole intervals since the start
+    pub fn vested(&self, time: Timestamp) -> Uint128 {
+        let schedule = &self.schedule;
+        if time <= schedule.start_time {
+            return Uint128::zero();
+        }
+        if time >= schedule.end_time {
+            return self.asset.amount;
+        }
+        let duration = schedule.end_time.seconds() - schedule.start_time.seconds();
+        let elapsed = time.seconds() - schedule.start_time.seconds();
+        let elapsed = elapsed - elapsed % schedule.interval;
+        self.asset.amount.multiply_ratio(elapsed, duration)
+    }
+
+    /// Vested amount not burned yet
+    pub fn releasable(&self, time: Timestamp) -> Uint128 {
+        match self.status {
+            StreamStatus::Active => self.vested(time) - self.released,
+            StreamStatus::Completed | StreamStatus::Cancelled => Uint128::zero(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CampaignTerms {
+    /// Matched amount per unit a user burns, `1` matches one to one
+    pub match_ratio: Decimal,
+    /// Most a single user can have matched over the whole campaign
+    pub per_user_cap: Uint128,
+    pub end_time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CampaignStatus {
+    Active,
+    /// Ended and the unused pool went back to the sponsor
+    Closed,
+}
+
+/// Sponsor pool matching burns of one asset, kept after closing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Campaign {
+    pub id: u64,
+    pub sponsor: Addr,
+    /// Escrowed pool, its asset info is the asset whose burns are matched
+    pub pool: Asset,
+    pub terms: CampaignTerms,
+    /// Burned from the pool so far
+    pub matched: Uint128,
+    /// Users that had a burn matched
+    pub participants: u64,
+    pub refunded: Uint128,
+    pub created_at: Timestamp,
+    pub status: CampaignStatus,
+    pub closed_at: Option<Timestamp>,
+}
+
+impl Campaign {
+    pub fn remaining(&self) -> Uint128 {
+        self.pool.amount - self.matched - self.refunded
+    }
+
+    pub fn is_running(&self, time: Timestamp) -> bool {
+        self.status == CampaignStatus::Active && time < self.terms.end_time && !self.remaining().is_zero()
+    }
+}
+
+pub struct CampaignIndexes<'a> {
+    pub asset: MultiIndex<'a, String, Campaign, u64>,
+}
+
+impl IndexList<Campaign> for CampaignIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Campaign>> + '_> {
+        let v: Vec<&dyn Index<Campaign>> = vec![&self.asset];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn campaigns<'a>() -> IndexedMap<u64, Campaign, CampaignIndexes<'a>> {
+    let indexes = CampaignIndexes {
+        asset: MultiIndex::new(|_, campaign| campaign.pool.info.to_string(), "campaigns", "campaigns__asset"),
+    };
+    IndexedMap::new("campaigns", indexes)
+}
+
+/// What burns of one asset referred by one referrer added up to
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ReferralStats {
+    pub referrer: Addr,
+    pub asset_info: AssetInfo,
+    pub burns: u64,
+    /// Amount that reached the burn auction, the referral cut excluded
+    pub burned: Uint128,
+    /// Referral cut paid to the referrer
+    pub paid: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DriveTerms {
+    /// Label shown by frontends, e.g. "Burn 100k X in March"
+    pub name: String,
+    /// Asset burns have to be of to count towards the drive
+    pub asset_info: AssetInfo,
+    pub goal: Uint128,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+}
+
+/// Community burn drive, burns tagged with its id count towards the goal
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Drive {
+    pub id: u64,
+    pub creator: Addr,
+    pub terms: DriveTerms,
+    pub burned: Uint128,
+    pub burns: u64,
+    pub contributors: u64,
+    pub created_at: Timestamp,
+    /// Block time of the burn that first took the drive to its goal
+    pub goal_reached_at: Option<Timestamp>,
+}
+
+impl Drive {
+    pub fn is_running(&self, time: Timestamp) -> bool {
+        self.terms.start_time <= time && time < self.terms.end_time
+    }
+}
+
+/// What one contributor burned towards a drive
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct DriveContribution {
+    pub drive_id: u64,
+    pub contributor: Addr,
+    pub burned: Uint128,
+    pub burns: u64,
+}
+
+pub struct DriveContributionIndexes<'a> {
+    pub burned: MultiIndex<'a, (u64, u128), DriveContribution, (u64, Addr)>,
+}
+
+impl IndexList<DriveContribution> for DriveContributionIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<DriveContribution>> + '_> {
+        let v: Vec<&dyn Index<DriveContribution>> = vec![&self.burned];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Contributions keyed by drive id and contributor, indexed by amount for the leaderboard
+pub fn drive_contributions<'a>() -> IndexedMap<(u64, Addr), DriveContribution, DriveContributionIndexes<'a>> {
+    let indexes = DriveContributionIndexes {
+        burned: MultiIndex::new(
+            |_, contribution| (contribution.drive_id, contribution.burned.u128()),
+            "drive_contributions",
+            "drive_contributions__burned",
+        ),
+    };
+    IndexedMap::new("drive_contributions", indexes)
+}
+
+/// CW20 this contract registered with the adapter, paid from the registration budget
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Cw20Registration {
+    pub cw20: Addr,
+    pub fee: Uint128,
+    pub triggered_by: Addr,
+    pub registered_at: Timestamp,
+    pub height: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Asset {
     pub info: AssetInfo,
@@ -0,0 +1,207 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult, SubMsg,
+};
+use cw_storage_plus::Bound;
+use injective_cosmwasm::InjectiveMsgWrapper;
+
+use crate::contract::burn_now;
+use crate::escrow::{assert_native_escrow, lock_escrow, refund_escrow, release_escrow};
+use crate::msg::StreamResponse;
+use crate::state::{Asset, BurnStream, StreamSchedule, StreamStatus, STREAMS, STREAM_COUNT};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+/// Escrows native coins attached as funds, exactly `asset` has to be attached
+pub fn create_native_stream(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    schedule: StreamSchedule,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    assert_native_escrow(&info, &asset)?;
+
+    create_stream(deps, env, info.sender, asset, schedule)
+}
+
+/// Records a stream for an asset this contract already holds
+pub fn create_stream(
+    deps: DepsMut,
+    env: Env,
+    owner: Addr,
+    asset: Asset,
+    schedule: StreamSchedule,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    if asset.amount.is_zero() {
+        return Err(StdError::generic_err("Stream amount must be greater than zero"));
+    }
+    if schedule.start_time < env.block.time {
+        return Err(StdError::generic_err("Stream cannot start in the past"));
+    }
+    if schedule.end_time <= schedule.start_time {
+        return Err(StdError::generic_err("Stream must end after it starts"));
+    }
+    let duration = schedule.end_time.seconds() - schedule.start_time.seconds();
+    if schedule.interval == 0 || schedule.interval > duration {
+        return Err(StdError::generic_err(format!(
+            "Stream interval must be between 1 and {} seconds",
+            duration
+        )));
+    }
+
+    let id = STREAM_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    STREAM_COUNT.save(deps.storage, &id)?;
+
+    let stream = BurnStream {
+        id,
+        owner,
+        asset,
+        schedule,
+        released: Default::default(),
+        refunded: Default::default(),
+        created_at: env.block.time,
+        status: StreamStatus::Active,
+        closed_at: None,
+    };
+    STREAMS.save(deps.storage, id, &stream)?;
+    lock_escrow(deps.storage, &stream.asset)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "create_stream")
+        .add_attribute("stream_id", id.to_string())
+        .add_attribute("owner", stream.owner)
+        .add_attribute("asset", stream.asset.to_string()))
+}
+
+/// Burns whatever vested since the last release, callable by anyone
+pub fn release_stream(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let mut stream = load_active_stream(deps.as_ref(), id)?;
+    let amount = stream.releasable(env.block.time);
+    if amount.is_zero() {
+        return Err(StdError::generic_err(format!("Nothing to release from stream {} yet", id)));
+    }
+
+    stream.released += amount;
+    if stream.released == stream.asset.amount {
+        stream.status = StreamStatus::Completed;
+        stream.closed_at = Some(env.block.time);
+    }
+    STREAMS.save(deps.storage, id, &stream)?;
+
+    let release = Asset {
+        info: stream.asset.info.clone(),
+        amount,
+    };
+    release_escrow(deps.storage, &release)?;
+    let mut messages: Vec<SubMsg<InjectiveMsgWrapper>> = vec![];
+    let mut events = vec![];
+    let receipt = burn_now(deps, &env, &info.sender, &stream.owner, release, &mut messages, &mut events)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_events(events)
+        .set_data(to_json_binary(&receipt)?)
+        .add_attribute("action", "release_stream")
+        .add_attribute("stream_id", id.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("released", stream.released.to_string()))
+}
+
+/// Stops a stream. What already vested is still burned, the unvested rest goes back to the owner.
+pub fn cancel_stream(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> StdResult<Response<InjectiveMsgWrapper>> {
+    let mut stream = load_active_stream(deps.as_ref(), id)?;
+    if info.sender != stream.owner {
+        return Err(StdError::generic_err("Unauthorized"));
+    }
+
+    let burn_amount = stream.releasable(env.block.time);
+    stream.released += burn_amount;
+    stream.refunded = stream.asset.amount - stream.released;
+    stream.status = StreamStatus::Cancelled;
+    stream.closed_at = Some(env.block.time);
+    STREAMS.save(deps.storage, id, &stream)?;
+    // Everything left in escrow is either burned or refunded below
+    release_escrow(
+        deps.storage,
+        &Asset {
+            info: stream.asset.info.clone(),
+            amount: burn_amount + stream.refunded,
+        },
+    )?;
+
+    let mut response = Response::new();
+    if !stream.refunded.is_zero() {
+        let refund = Asset {
+            info: stream.asset.info.clone(),
+            amount: stream.refunded,
+        };
+        response = response.add_message(refund_escrow(refund, stream.owner.clone())?);
+    }
+    if !burn_amount.is_zero() {
+        let release = Asset {
+            info: stream.asset.info.clone(),
+            amount: burn_amount,
+        };
+        let mut messages: Vec<SubMsg<InjectiveMsgWrapper>> = vec![];
+        let mut events = vec![];
+        let receipt = burn_now(deps, &env, &info.sender, &stream.owner, release, &mut messages, &mut events)?;
+        response = response
+            .add_submessages(messages)
+            .add_events(events)
+            .set_data(to_json_binary(&receipt)?);
+    }
+
+    Ok(response
+        .add_attribute("action", "cancel_stream")
+        .add_attribute("stream_id", id.to_string())
+        .add_attribute("burned", burn_amount.to_string())
+        .add_attribute("refunded", stream.refunded.to_string()))
+}
+
+fn load_active_stream(deps: Deps, id: u64) -> StdResult<BurnStream> {
+    let stream = STREAMS.load(deps.storage, id)?;
+    if stream.status != StreamStatus::Active {
+        return Err(StdError::generic_err(format!("Stream {} is no longer active", id)));
+    }
+    Ok(stream)
+}
+
+fn stream_response(env: &Env, stream: BurnStream) -> StreamResponse {
+    // Closed streams stop vesting, whatever they burned is all that vested
+    let vested = match stream.status {
+        StreamStatus::Active => stream.vested(env.block.time),
+        StreamStatus::Completed | StreamStatus::Cancelled => stream.released,
+    };
+    StreamResponse {
+        vested,
+        releasable: stream.releasable(env.block.time),
+        stream,
+    }
+}
+
+pub fn query_stream(deps: Deps, env: Env, id: u64) -> StdResult<Binary> {
+    to_json_binary(&stream_response(&env, STREAMS.load(deps.storage, id)?))
+}
+
+pub fn query_streams(deps: Deps, env: Env, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let streams = STREAMS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, stream)| stream_response(&env, stream)))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_json_binary(&streams)
+}
@@ -1,16 +1,53 @@
 #![cfg(test)]
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
-    use cosmwasm_std::testing::{mock_dependencies, mock_env};
-    use cosmwasm_std::{Addr, MessageInfo, Binary, from_json, Coin};
-    use cw20::{Cw20ReceiveMsg};
+    use std::marker::PhantomData;
+
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{
+        Addr, Api, MessageInfo, Binary, from_json, to_json_binary, BankMsg, Coin, ContractResult, CosmosMsg, Deps, DepsMut, Env, OwnedDeps, Reply,
+        ReplyOn, SubMsgResponse, SubMsgResult, SystemResult, Uint128, WasmQuery,
+    };
+    use cw20::{BalanceResponse, Cw20ReceiveMsg};
+    use injective_cosmwasm::{InjectiveMsg, InjectiveMsgWrapper, InjectiveQuery, InjectiveQueryWrapper};
+    use injective_cosmwasm::exchange::response::SubaccountDepositResponse;
+    use injective_cosmwasm::exchange::subaccount::checked_address_to_subaccount_id;
+    use injective_cosmwasm::exchange::types::Deposit;
+    use injective_cosmwasm::exchange::order::OrderType;
+    use injective_cosmwasm::exchange::types::MarketId;
+    use injective_math::FPDecimal;
+    use injective_cosmwasm::exchange::types::SubaccountId;
+    use injective_cosmwasm::InjectiveRoute;
+    use injective_std::types::cosmos::base::v1beta1::Coin as ProtoCoin;
+    use injective_std::types::injective::exchange::v1beta1::{
+        MsgCreateSpotMarketOrderResponse, MsgDeposit, MsgExternalTransfer, MsgWithdraw, SpotMarketOrderResults,
+    };
 
     use crate::msg::InstantiateMsg;
     use crate::instantiate;
-    use crate::state::{load_config, Asset, AssetInfo, Config};
-    use crate::msg::{ExecuteMsg, QueryMsg};
-    use crate::contract::{execute, query};
+    use crate::state::{load_config, Asset, AssetInfo, BurnPermit, Config, MessageBackend, BlockPoint, PledgeTerms, RateLimit, RescueItem, RescueRecord, RescueStatus, StreamSchedule, StreamStatus, SENDER_ACTIVITY, SubaccountRouting, SwapRoute};
+    use crate::msg::{parse_burn_receipt, BurnReceipt, Callback, CallbackExecuteMsg, Cw20HookMsg, ExecuteMsg, HoldingsResponse, HookExecuteMsg, HooksResponse, PermitNonceResponse, QueryMsg, RecoveryDestination, SenderRateLimitResponse, SimulateBurnResponse, StreamResponse, SubaccountRoutingResponse};
+    use crate::error::ContractError;
+    use crate::hooks::{HOOK_GAS_LIMIT, HOOK_REPLY_ID};
+    use crate::permit::{permit_digest, permit_owner};
+    use crate::registration::AdapterQueryMsg;
+    use crate::contract::{execute, query, reply, CALLBACK_GAS_LIMIT, CALLBACK_REPLY_ID, SWAP_REPLY_ID};
+
+    /// Answers the adapter's registration queries, `registered` CW20s need no registration
+    fn mock_adapter_queries(querier: &mut MockQuerier, registered: Vec<String>, fee: u128) {
+        querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let res = match from_json(msg).unwrap() {
+                    AdapterQueryMsg::RegisteredContracts {} => to_json_binary(&registered),
+                    AdapterQueryMsg::NewDenomFee {} => to_json_binary(&Uint128::new(fee)),
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => panic!("Unexpected wasm query"),
+        });
+    }
 
     #[test]
     fn test_instantiate_contract() {
@@ -118,7 +155,7 @@ mod tests {
             },
             amount: 1000u128.into(),
         };
-        let execute_msg = ExecuteMsg::SendNative { asset };
+        let execute_msg = ExecuteMsg::SendNative { asset, callback: None, referrer: None, drive_id: None, idempotency_key: None, deadline: None };
     
         // Call the execute function
         let res = execute(deps.as_mut(), env.clone(), admin_info, execute_msg).unwrap();
@@ -134,6 +171,59 @@ mod tests {
         // }
     }
 
+    #[test]
+    fn test_certificate_skipped_without_auction_round() {
+        // The mock querier answers no stargate queries, so the auction round lookup fails
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked("inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8");
+
+        let admin = deps.api.addr_make("admin");
+        let admin_info = MessageInfo {
+            sender: admin.clone(),
+            funds: vec![],
+        };
+        let msg = InstantiateMsg {
+            admin: admin.to_string(),
+            adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+            burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin_info.clone(), msg).unwrap();
+        let set_contract_msg = ExecuteMsg::SetCertificateContract {
+            contract: Some(deps.api.addr_make("certificates").to_string()),
+        };
+        execute(deps.as_mut(), env.clone(), admin_info.clone(), set_contract_msg).unwrap();
+        let set_threshold_msg = ExecuteMsg::SetCertificateThreshold {
+            asset_info: AssetInfo::NativeToken { denom: "inj".to_string() },
+            threshold: Some(100u128.into()),
+        };
+        execute(deps.as_mut(), env.clone(), admin_info, set_threshold_msg).unwrap();
+
+        let burner_info = MessageInfo {
+            sender: deps.api.addr_make("burner"),
+            funds: vec![Coin::new(1_000u128, "inj")],
+        };
+        let execute_msg = ExecuteMsg::SendNative {
+            asset: Asset {
+                info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                amount: 1_000u128.into(),
+            },
+            callback: None,
+            referrer: None,
+            drive_id: None,
+            idempotency_key: None,
+            deadline: None,
+        };
+        let res = execute(deps.as_mut(), env, burner_info, execute_msg).unwrap();
+
+        // The burn goes through with no mint, the skipped certificate is logged instead
+        assert_eq!(res.messages.len(), 2);
+        assert!(res.messages.iter().all(|msg| !matches!(msg.msg, CosmosMsg::Wasm(_))));
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "certificate_mint_failed");
+        assert_eq!(res.events[0].attributes[0].value, "1");
+    }
+
     #[test]
     fn test_receive_cw20_via_execute() {
         let mut deps = mock_dependencies();
@@ -159,6 +249,7 @@ mod tests {
         let cw20_sender = "inj1sendercw20address0000000000000000000000000000000";
         let cw20_contract = "inj1cw20contractaddress000000000000000000000000000";
         let cw20_amount = 1000u128;
+        mock_adapter_queries(&mut deps.querier, vec![cw20_contract.to_string()], 0);
 
         let receive_msg = Cw20ReceiveMsg {
             sender: cw20_sender.to_string(),
@@ -174,7 +265,7 @@ mod tests {
         };
 
         // Call the execute function
-        let res = execute(deps.as_mut(), env.clone(), cw20_info, execute_msg).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), cw20_info.clone(), execute_msg.clone()).unwrap();
 
         // Assert the response attributes
         assert_eq!(
@@ -192,6 +283,11 @@ mod tests {
         for (i, msg) in res.messages.iter().enumerate() {
             println!("Message {}: {:?}", i + 1, msg);
         }
+
+        // The adapter's registry is only queried once per token, later burns use the cached answer
+        deps.querier.update_wasm(|_| panic!("Unexpected wasm query"));
+        let res = execute(deps.as_mut(), env, cw20_info, execute_msg).unwrap();
+        assert_eq!(res.messages.len(), 3);
     }
 
     #[test]
@@ -255,6 +351,11 @@ mod tests {
         // Prepare the ExecuteMsg::SendNative message
         let execute_msg = ExecuteMsg::SendNative {
             asset: invalid_asset,
+            callback: None,
+            referrer: None,
+            drive_id: None,
+            idempotency_key: None,
+            deadline: None,
         };
 
         // Call the execute function and expect an error
@@ -263,4 +364,1350 @@ mod tests {
         // Assert the error message
         assert_eq!(err.to_string(), "Generic error: Invalid asset: Expected a native token");
     }
+
+    #[test]
+    fn test_swap_then_burn() {
+        let mut deps = mock_dependencies();
+
+        let contract_address = "inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8";
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(contract_address);
+
+        let admin_info = MessageInfo {
+            sender: Addr::unchecked("inj1q2m26a7jdzjyfdn545vqsude3zwwtfrdap5jgz"),
+            funds: vec![],
+        };
+
+        // Instantiate the contract
+        let msg = InstantiateMsg {
+            admin: "inj1q2m26a7jdzjyfdn545vqsude3zwwtfrdap5jgz".to_string(),
+            adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+            burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin_info.clone(), msg).unwrap();
+
+        let route = SwapRoute {
+            market_id: MarketId::unchecked("0x0611780ba69656949525013d947713300f56c37b6175e02f26bffa495c3208fe"),
+            quote_denom: "inj".to_string(),
+            worst_price: FPDecimal::must_from_str("0.5"),
+            min_quantity_tick_size: FPDecimal::must_from_str("1000"),
+        };
+
+        // Only the admin can configure swap routes
+        let set_route_msg = ExecuteMsg::SetSwapRoute {
+            denom: "lp".to_string(),
+            route: route.clone(),
+        };
+        let user_info = MessageInfo {
+            sender: Addr::unchecked("user"),
+            funds: vec![Coin {
+                denom: "lp".to_string(),
+                amount: 10_500u128.into(),
+            }],
+        };
+        let err = execute(deps.as_mut(), env.clone(), user_info.clone(), set_route_msg.clone()).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Unauthorized");
+        execute(deps.as_mut(), env.clone(), admin_info, set_route_msg).unwrap();
+
+        let routes: Vec<crate::msg::SwapRouteResponse> =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetSwapRoutes {}).unwrap()).unwrap();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].route, route);
+
+        // Burning a routed denom deposits it, burns what is below a tick and sells the rest
+        let execute_msg = ExecuteMsg::SendNative {
+            asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "lp".to_string(),
+                },
+                amount: 10_500u128.into(),
+            },
+            callback: None,
+            referrer: None,
+            drive_id: None,
+            idempotency_key: None,
+            deadline: None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), user_info, execute_msg).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        match &res.messages[1].msg {
+            CosmosMsg::Custom(InjectiveMsgWrapper {
+                msg_data: InjectiveMsg::ExternalTransfer { amount, .. },
+                ..
+            }) => {
+                assert_eq!(amount.denom, "lp");
+                assert_eq!(amount.amount, Uint128::new(500));
+            }
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+        assert_eq!(res.messages[2].id, SWAP_REPLY_ID);
+        assert_eq!(res.messages[2].reply_on, ReplyOn::Success);
+        match &res.messages[2].msg {
+            CosmosMsg::Custom(InjectiveMsgWrapper {
+                msg_data: InjectiveMsg::CreateSpotMarketOrder { order, .. },
+                ..
+            }) => {
+                assert_eq!(order.order_type, OrderType::SellAtomic);
+                assert_eq!(order.order_info.price, route.worst_price);
+                assert_eq!(order.order_info.quantity, FPDecimal::must_from_str("10000"));
+            }
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+
+        // The reply burns the filled proceeds net of fees
+        let order_response = MsgCreateSpotMarketOrderResponse {
+            order_hash: "0xabc".to_string(),
+            results: Some(SpotMarketOrderResults {
+                quantity: "10000000000000000000000".to_string(),
+                price: "600000000000000000".to_string(),
+                fee: "6000000000000000000".to_string(),
+            }),
+            cid: "".to_string(),
+        };
+        #[allow(deprecated)]
+        let reply_msg = Reply {
+            id: SWAP_REPLY_ID,
+            payload: res.messages[2].payload.clone(),
+            gas_used: 0,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary::from(order_response)),
+                msg_responses: vec![],
+            }),
+        };
+        let res = reply(deps.as_mut(), env, reply_msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        match &res.messages[0].msg {
+            CosmosMsg::Custom(InjectiveMsgWrapper {
+                msg_data: InjectiveMsg::ExternalTransfer { amount, .. },
+                ..
+            }) => {
+                assert_eq!(amount.denom, "inj");
+                assert_eq!(amount.amount, Uint128::new(5994));
+            }
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn test_holdings_query() {
+        let contract_address = "inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8";
+        let querier = MockQuerier::<InjectiveQueryWrapper>::new(&[(
+            contract_address,
+            &[Coin {
+                denom: "inj".to_string(),
+                amount: 500u128.into(),
+            }],
+        )])
+        .with_custom_handler(|query| match &query.query_data {
+            InjectiveQuery::SubaccountDeposit { denom, .. } => {
+                let amount = if denom == "stuck" { "42" } else { "0" };
+                let res = SubaccountDepositResponse {
+                    deposits: Deposit {
+                        available_balance: FPDecimal::must_from_str(amount),
+                        total_balance: FPDecimal::must_from_str(amount),
+                    },
+                };
+                SystemResult::Ok(ContractResult::Ok(to_json_binary(&res).unwrap()))
+            }
+            _ => panic!("Unexpected custom query"),
+        });
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier,
+            custom_query_type: PhantomData,
+        };
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { .. } => SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&BalanceResponse {
+                    balance: 77u128.into(),
+                })
+                .unwrap(),
+            )),
+            _ => panic!("Unexpected wasm query"),
+        });
+
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(contract_address);
+        let admin = deps.api.addr_make("admin");
+        let token = deps.api.addr_make("token").to_string();
+        let admin_info = MessageInfo {
+            sender: admin.clone(),
+            funds: vec![],
+        };
+
+        // Instantiate the contract
+        let msg = InstantiateMsg {
+            admin: admin.to_string(),
+            adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+            burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin_info.clone(), msg).unwrap();
+
+        let track_msg = ExecuteMsg::SetTrackedCw20s {
+            tokens: vec![token.clone()],
+        };
+        execute(deps.as_mut(), env.clone(), admin_info, track_msg).unwrap();
+
+        // Query with the configured lists and an explicit denom to check in the subaccount
+        let query_msg = QueryMsg::Holdings {
+            cw20s: None,
+            denoms: Some(vec!["inj".to_string(), "stuck".to_string()]),
+        };
+        let res: HoldingsResponse = from_json(query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+
+        assert_eq!(res.bank, vec![Coin { denom: "inj".to_string(), amount: 500u128.into() }]);
+        assert_eq!(res.cw20.len(), 1);
+        assert_eq!(res.cw20[0].contract_addr, token);
+        assert_eq!(res.cw20[0].balance, Uint128::new(77));
+        assert_eq!(res.subaccount_deposits.len(), 1);
+        assert_eq!(res.subaccount_deposits[0].denom, "stuck");
+        assert_eq!(res.subaccount_deposits[0].total_balance, FPDecimal::must_from_str("42"));
+    }
+
+    #[test]
+    fn test_recover_subaccount_funds() {
+        let mut deps = mock_dependencies();
+
+        let contract_address = "inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8";
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(contract_address);
+
+        let admin = deps.api.addr_make("admin");
+        let admin_info = MessageInfo {
+            sender: admin.clone(),
+            funds: vec![],
+        };
+
+        // Instantiate the contract
+        let msg = InstantiateMsg {
+            admin: admin.to_string(),
+            adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+            burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin_info.clone(), msg).unwrap();
+
+        let amounts = vec![
+            Coin { denom: "inj".to_string(), amount: 10u128.into() },
+            Coin { denom: "peggy0xdac17f958d2ee523a2206206994597c13d831ec7".to_string(), amount: 20u128.into() },
+        ];
+
+        // Only the admin can recover funds
+        let recover_msg = ExecuteMsg::RecoverSubaccountFunds {
+            nonce: 1,
+            amounts: amounts.clone(),
+            destination: RecoveryDestination::Address {
+                address: deps.api.addr_make("treasury").to_string(),
+            },
+        };
+        let user_info = MessageInfo {
+            sender: deps.api.addr_make("user"),
+            funds: vec![],
+        };
+        let err = execute(deps.as_mut(), env.clone(), user_info, recover_msg.clone()).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Unauthorized");
+
+        // Back to an address: one withdraw per denom followed by a single bank send
+        let res = execute(deps.as_mut(), env.clone(), admin_info.clone(), recover_msg).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert!(matches!(res.messages[2].msg, CosmosMsg::Bank(_)));
+        assert_eq!(res.events.len(), 2);
+        assert_eq!(res.events[1].ty, "subaccount_recovery");
+
+        // On to the burn auction from a subaccount burns are no longer routed through
+        let recover_msg = ExecuteMsg::RecoverSubaccountFunds {
+            nonce: 2,
+            amounts,
+            destination: RecoveryDestination::BurnAuction {},
+        };
+        let res = execute(deps.as_mut(), env.clone(), admin_info.clone(), recover_msg).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        for msg in res.messages.iter() {
+            assert!(matches!(
+                msg.msg,
+                CosmosMsg::Custom(InjectiveMsgWrapper {
+                    msg_data: InjectiveMsg::ExternalTransfer { .. },
+                    ..
+                })
+            ));
+        }
+        let older_subaccount_id = checked_address_to_subaccount_id(&env.contract.address, 2);
+        assert!(res.events[0]
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "subaccount_id" && attr.value == older_subaccount_id.as_str()));
+
+        // The default subaccount is the bank balance, escrowed funds can't be recovered from it
+        deps.querier.bank.update_balance(env.contract.address.clone(), vec![Coin::new(1_000u128, "inj")]);
+        let pledge_msg = ExecuteMsg::CreatePledge {
+            asset: Asset {
+                info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                amount: 800u128.into(),
+            },
+            terms: PledgeTerms {
+                unlock: BlockPoint::AtHeight { height: env.block.height + 100 },
+                cancellable_until: None,
+            },
+        };
+        let pledger_info = MessageInfo {
+            sender: deps.api.addr_make("pledger"),
+            funds: vec![Coin::new(800u128, "inj")],
+        };
+        execute(deps.as_mut(), env.clone(), pledger_info, pledge_msg).unwrap();
+        for destination in [
+            RecoveryDestination::Address {
+                address: deps.api.addr_make("treasury").to_string(),
+            },
+            RecoveryDestination::BurnAuction {},
+        ] {
+            let recover_msg = ExecuteMsg::RecoverSubaccountFunds {
+                nonce: 0,
+                amounts: vec![Coin::new(201u128, "inj")],
+                destination,
+            };
+            let err = execute(deps.as_mut(), env.clone(), admin_info.clone(), recover_msg).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Generic error: Only 200inj is not reserved for escrows or the registration budget"
+            );
+        }
+
+        // Unreserved bank funds go out with a plain bank send, there is nothing to withdraw
+        let recover_msg = ExecuteMsg::RecoverSubaccountFunds {
+            nonce: 0,
+            amounts: vec![Coin::new(200u128, "inj")],
+            destination: RecoveryDestination::Address {
+                address: deps.api.addr_make("treasury").to_string(),
+            },
+        };
+        let res = execute(deps.as_mut(), env, admin_info, recover_msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert!(matches!(res.messages[0].msg, CosmosMsg::Bank(_)));
+    }
+
+    #[test]
+    fn test_timelocked_rescue() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let admin = deps.api.addr_make("admin");
+        let recipient = deps.api.addr_make("recipient").to_string();
+        let admin_info = MessageInfo {
+            sender: admin.clone(),
+            funds: vec![],
+        };
+
+        // Instantiate the contract
+        let msg = InstantiateMsg {
+            admin: admin.to_string(),
+            adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+            burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin_info.clone(), msg).unwrap();
+
+        // The timelock cannot be shortened below a day
+        let set_timelock_msg = ExecuteMsg::SetRescueTimelock { seconds: 3600 };
+        let err = execute(deps.as_mut(), env.clone(), admin_info.clone(), set_timelock_msg).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Rescue timelock must be at least 86400 seconds");
+        let set_timelock_msg = ExecuteMsg::SetRescueTimelock { seconds: 86_400 };
+        execute(deps.as_mut(), env.clone(), admin_info.clone(), set_timelock_msg).unwrap();
+
+        // The contract holds 100 tokens sent by mistake, and 1000 inj of which 800 are pledged
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { .. } => SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&BalanceResponse {
+                    balance: 100u128.into(),
+                })
+                .unwrap(),
+            )),
+            _ => panic!("Unexpected wasm query"),
+        });
+        deps.querier.bank.update_balance(env.contract.address.clone(), vec![Coin::new(1_000u128, "inj")]);
+        let pledge_msg = ExecuteMsg::CreatePledge {
+            asset: Asset {
+                info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                amount: 800u128.into(),
+            },
+            terms: PledgeTerms {
+                unlock: BlockPoint::AtHeight { height: env.block.height + 100 },
+                cancellable_until: None,
+            },
+        };
+        let pledger_info = MessageInfo {
+            sender: deps.api.addr_make("pledger"),
+            funds: vec![Coin::new(800u128, "inj")],
+        };
+        execute(deps.as_mut(), env.clone(), pledger_info, pledge_msg).unwrap();
+        let propose_msg = ExecuteMsg::ProposeRescue {
+            item: RescueItem::Asset {
+                asset: Asset {
+                    info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                    amount: 201u128.into(),
+                },
+            },
+            recipient: recipient.clone(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), admin_info.clone(), propose_msg).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Generic error: Only 200inj is not reserved for escrows or the registration budget"
+        );
+
+        // Propose returning a mistakenly sent CW20
+        let propose_msg = ExecuteMsg::ProposeRescue {
+            item: RescueItem::Asset {
+                asset: Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: deps.api.addr_make("token").to_string(),
+                    },
+                    amount: 100u128.into(),
+                },
+            },
+            recipient: recipient.clone(),
+        };
+        execute(deps.as_mut(), env.clone(), admin_info.clone(), propose_msg).unwrap();
+
+        // and an NFT, which gets cancelled
+        let propose_msg = ExecuteMsg::ProposeRescue {
+            item: RescueItem::Cw721 {
+                contract_addr: deps.api.addr_make("nft").to_string(),
+                token_id: "1".to_string(),
+            },
+            recipient,
+        };
+        execute(deps.as_mut(), env.clone(), admin_info.clone(), propose_msg).unwrap();
+        execute(deps.as_mut(), env.clone(), admin_info.clone(), ExecuteMsg::CancelRescue { id: 2 }).unwrap();
+
+        // The timelock has to pass before the rescue can be executed
+        let err = execute(deps.as_mut(), env.clone(), admin_info.clone(), ExecuteMsg::ExecuteRescue { id: 1 }).unwrap_err();
+        assert!(err.to_string().contains("timelocked"));
+
+        env.block.time = env.block.time.plus_seconds(86_400);
+        let res = execute(deps.as_mut(), env.clone(), admin_info.clone(), ExecuteMsg::ExecuteRescue { id: 1 }).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        let err = execute(deps.as_mut(), env.clone(), admin_info, ExecuteMsg::ExecuteRescue { id: 2 }).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Rescue 2 is no longer pending");
+
+        // The log keeps both entries
+        let query_msg = QueryMsg::GetRescues { start_after: None, limit: None };
+        let rescues: Vec<RescueRecord> = from_json(query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+        assert_eq!(rescues.len(), 2);
+        assert_eq!(rescues[0].status, RescueStatus::Executed);
+        assert_eq!(rescues[1].status, RescueStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_send_batch_via_execute() {
+        let mut deps = mock_dependencies();
+
+        let contract_address = "inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8";
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(contract_address);
+
+        let admin_info = MessageInfo {
+            sender: Addr::unchecked("inj1q2m26a7jdzjyfdn545vqsude3zwwtfrdap5jgz"),
+            funds: vec![],
+        };
+
+        // Instantiate the contract
+        let msg = InstantiateMsg {
+            admin: "inj1q2m26a7jdzjyfdn545vqsude3zwwtfrdap5jgz".to_string(),
+            adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+            burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin_info, msg).unwrap();
+
+        let native = Asset {
+            info: AssetInfo::NativeToken {
+                denom: "inj".to_string(),
+            },
+            amount: 1000u128.into(),
+        };
+        let token = Asset {
+            info: AssetInfo::Token {
+                contract_addr: deps.api.addr_make("token").to_string(),
+            },
+            amount: 500u128.into(),
+        };
+        mock_adapter_queries(&mut deps.querier, vec![deps.api.addr_make("token").to_string()], 0);
+        let bot_info = MessageInfo {
+            sender: deps.api.addr_make("treasury_bot"),
+            funds: vec![Coin {
+                denom: "inj".to_string(),
+                amount: 1000u128.into(),
+            }],
+        };
+
+        // Deposit and Transfer for the coin, TransferFrom, adapter Send, Deposit and Transfer for the CW20
+        let execute_msg = ExecuteMsg::SendBatch {
+            assets: vec![native.clone(), token.clone()],
+        };
+        let res = execute(deps.as_mut(), env.clone(), bot_info.clone(), execute_msg).unwrap();
+        assert_eq!(res.messages.len(), 6);
+        match &res.messages[2].msg {
+            CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute { msg, .. }) => {
+                let msg: cw20::Cw20ExecuteMsg = from_json(msg).unwrap();
+                assert_eq!(
+                    msg,
+                    cw20::Cw20ExecuteMsg::TransferFrom {
+                        owner: bot_info.sender.to_string(),
+                        recipient: contract_address.to_string(),
+                        amount: 500u128.into(),
+                    }
+                );
+            }
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+
+        // Funds that are not part of the batch are rejected
+        let execute_msg = ExecuteMsg::SendBatch {
+            assets: vec![token.clone()],
+        };
+        let err = execute(deps.as_mut(), env.clone(), bot_info.clone(), execute_msg).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Unexpected funds provided: 1000inj");
+
+        let execute_msg = ExecuteMsg::SendBatch {
+            assets: vec![native.clone(), token, native],
+        };
+        let err = execute(deps.as_mut(), env, bot_info, execute_msg).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Duplicate asset: inj");
+    }
+
+    #[test]
+    fn test_burn_hooks() {
+        let mut deps = mock_dependencies();
+
+        let contract_address = "inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8";
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(contract_address);
+
+        let admin = deps.api.addr_make("admin");
+        let admin_info = MessageInfo {
+            sender: admin.clone(),
+            funds: vec![],
+        };
+
+        // Instantiate the contract
+        let msg = InstantiateMsg {
+            admin: admin.to_string(),
+            adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+            burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin_info.clone(), msg).unwrap();
+
+        let loyalty = deps.api.addr_make("loyalty").to_string();
+        let minter = deps.api.addr_make("minter").to_string();
+        for hook in [&loyalty, &minter] {
+            let add_msg = ExecuteMsg::AddHook { contract: hook.clone() };
+            execute(deps.as_mut(), env.clone(), admin_info.clone(), add_msg).unwrap();
+        }
+        execute(deps.as_mut(), env.clone(), admin_info.clone(), ExecuteMsg::SetHookAutoRemove { enabled: true }).unwrap();
+
+        // Every burn notifies each hook after the burn messages, failures are caught in the reply
+        let user_info = MessageInfo {
+            sender: deps.api.addr_make("user"),
+            funds: vec![Coin {
+                denom: "inj".to_string(),
+                amount: 1000u128.into(),
+            }],
+        };
+        let asset = Asset {
+            info: AssetInfo::NativeToken {
+                denom: "inj".to_string(),
+            },
+            amount: 1000u128.into(),
+        };
+        let execute_msg = ExecuteMsg::SendNative { asset: asset.clone(), callback: None, referrer: None, drive_id: None, idempotency_key: None, deadline: None };
+        let res = execute(deps.as_mut(), env.clone(), user_info.clone(), execute_msg).unwrap();
+        assert_eq!(res.messages.len(), 4);
+        assert_eq!(res.messages[2].reply_on, ReplyOn::Error);
+        assert_eq!(res.messages[2].gas_limit, Some(HOOK_GAS_LIMIT));
+        match &res.messages[2].msg {
+            CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute { contract_addr, msg, .. }) => {
+                assert_eq!(contract_addr, &loyalty);
+                let hook_msg: HookExecuteMsg = from_json(msg).unwrap();
+                assert_eq!(
+                    hook_msg,
+                    HookExecuteMsg::BurnHook {
+                        sender: user_info.sender.clone(),
+                        beneficiary: user_info.sender.clone(),
+                        asset,
+                        auction_denom: "inj".to_string(),
+                    }
+                );
+            }
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+
+        // A failing listener gets logged and dropped from the registry
+        let reply_msg = Reply {
+            id: HOOK_REPLY_ID,
+            payload: res.messages[2].payload.clone(),
+            gas_used: 0,
+            result: SubMsgResult::Err("out of gas".to_string()),
+        };
+        let res = reply(deps.as_mut(), env.clone(), reply_msg).unwrap();
+        assert!(res.attributes.contains(&cosmwasm_std::Attribute::new("removed", "true")));
+
+        let hooks: HooksResponse = from_json(query(deps.as_ref(), env, QueryMsg::GetHooks {}).unwrap()).unwrap();
+        assert_eq!(hooks.hooks, vec![minter]);
+    }
+
+    #[test]
+    fn test_burn_callback() {
+        let mut deps = mock_dependencies();
+        deps.api = MockApi::default().with_prefix("inj");
+
+        let contract_address = Addr::unchecked("inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8");
+        let mut env = mock_env();
+        env.contract.address = contract_address.clone();
+
+        let admin = deps.api.addr_make("admin");
+        let admin_info = MessageInfo {
+            sender: admin.clone(),
+            funds: vec![],
+        };
+
+        // Instantiate the contract
+        let msg = InstantiateMsg {
+            admin: admin.to_string(),
+            adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+            burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin_info.clone(), msg).unwrap();
+
+        let protocol = deps.api.addr_make("protocol");
+        let cw20_info = MessageInfo {
+            sender: deps.api.addr_make("token"),
+            funds: vec![],
+        };
+        mock_adapter_queries(&mut deps.querier, vec![cw20_info.sender.to_string()], 0);
+        let callback = Callback {
+            contract: protocol.to_string(),
+            msg: Binary::from(b"{\"unlock\":{}}"),
+        };
+
+        // The callback is never allowed to target the burn contract itself
+        let receive_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: protocol.to_string(),
+            amount: 1000u128.into(),
+            msg: cosmwasm_std::to_json_binary(&Cw20HookMsg {
+                callback: Some(Callback {
+                    contract: contract_address.to_string(),
+                    msg: Binary::default(),
+                }),
+                ..Default::default()
+            })
+            .unwrap(),
+        });
+        let err = execute(deps.as_mut(), env.clone(), cw20_info.clone(), receive_msg).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Burn callback cannot target this contract");
+
+        // The callback runs last, with a gas limit, and wraps the original msg with the burn details
+        let receive_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: protocol.to_string(),
+            amount: 1000u128.into(),
+            msg: cosmwasm_std::to_json_binary(&Cw20HookMsg {
+                callback: Some(callback.clone()),
+                ..Default::default()
+            })
+            .unwrap(),
+        });
+        let res = execute(deps.as_mut(), env.clone(), cw20_info.clone(), receive_msg.clone()).unwrap();
+        assert_eq!(res.messages.len(), 4);
+        let callback_msg = &res.messages[3];
+        assert_eq!(callback_msg.id, CALLBACK_REPLY_ID);
+        assert_eq!(callback_msg.reply_on, ReplyOn::Always);
+        assert_eq!(callback_msg.gas_limit, Some(CALLBACK_GAS_LIMIT));
+        match &callback_msg.msg {
+            CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute { contract_addr, msg, .. }) => {
+                assert_eq!(contract_addr, protocol.as_str());
+                let CallbackExecuteMsg::BurnCallback { sender, asset, msg, .. } = from_json(msg).unwrap();
+                assert_eq!(sender, protocol);
+                assert_eq!(asset.amount, Uint128::new(1000));
+                assert_eq!(msg, callback.msg);
+            }
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+
+        // Until the callback replies the contract refuses to be re-entered
+        let err = execute(deps.as_mut(), env.clone(), cw20_info.clone(), receive_msg.clone()).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Re-entry during a burn callback is not allowed");
+
+        let failed_reply = Reply {
+            id: CALLBACK_REPLY_ID,
+            payload: Binary::default(),
+            gas_used: CALLBACK_GAS_LIMIT,
+            result: SubMsgResult::Err("out of gas".to_string()),
+        };
+        let err = reply(deps.as_mut(), env.clone(), failed_reply).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Burn callback failed: out of gas");
+
+        execute(deps.as_mut(), env, cw20_info, receive_msg).unwrap();
+    }
+
+    #[test]
+    fn test_burn_receipt_data() {
+        let mut deps = mock_dependencies();
+
+        let contract_address = "inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8";
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(contract_address);
+
+        let admin_info = MessageInfo {
+            sender: Addr::unchecked("inj1q2m26a7jdzjyfdn545vqsude3zwwtfrdap5jgz"),
+            funds: vec![],
+        };
+
+        // Instantiate the contract
+        let msg = InstantiateMsg {
+            admin: "inj1q2m26a7jdzjyfdn545vqsude3zwwtfrdap5jgz".to_string(),
+            adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+            burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin_info, msg).unwrap();
+
+        let cw20_info = MessageInfo {
+            sender: Addr::unchecked("inj1cw20contractaddress000000000000000000000000000"),
+            funds: vec![],
+        };
+        mock_adapter_queries(&mut deps.querier, vec![cw20_info.sender.to_string()], 0);
+        let receive_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "inj1sendercw20address0000000000000000000000000000000".to_string(),
+            amount: 1000u128.into(),
+            msg: Binary::default(),
+        });
+        execute(deps.as_mut(), env.clone(), cw20_info.clone(), receive_msg.clone()).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), cw20_info, receive_msg).unwrap();
+
+        // Burn ids keep counting across burns
+        let receipt: BurnReceipt = from_json(res.data.clone().unwrap()).unwrap();
+        assert_eq!(receipt.burn_id, 2);
+        assert_eq!(receipt.payer, Addr::unchecked("inj1sendercw20address0000000000000000000000000000000"));
+        assert_eq!(
+            receipt.auction_denom,
+            "factory/inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk/inj1cw20contractaddress000000000000000000000000000"
+        );
+        assert_eq!(
+            receipt.destination_subaccount,
+            "0x1111111111111111111111111111111111111111111111111111111111111111"
+        );
+        assert_eq!(receipt.height, env.block.height);
+
+        // A calling contract sees the data wrapped in a MsgExecuteContractResponse
+        let data = res.data.unwrap().to_vec();
+        let mut encoded = vec![0x0a];
+        let mut len = data.len();
+        while len >= 0x80 {
+            encoded.push((len as u8 & 0x7f) | 0x80);
+            len >>= 7;
+        }
+        encoded.push(len as u8);
+        encoded.extend(data);
+
+        #[allow(deprecated)]
+        let response = SubMsgResponse {
+            events: vec![],
+            data: Some(Binary::from(encoded)),
+            msg_responses: vec![],
+        };
+        assert_eq!(parse_burn_receipt(&response).unwrap(), receipt);
+    }
+
+    #[test]
+    fn test_simulate_burn_query() {
+        let mut deps = mock_dependencies();
+        deps.api = MockApi::default().with_prefix("inj");
+
+        let contract_address = "inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8";
+        let adapter = "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk";
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(contract_address);
+        let admin_info = MessageInfo {
+            sender: deps.api.addr_make("admin"),
+            funds: vec![],
+        };
+
+        let msg = InstantiateMsg {
+            admin: admin_info.sender.to_string(),
+            adapter_contract: adapter.to_string(),
+            burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+        };
+        instantiate(deps.as_mut(), env.clone(), admin_info, msg).unwrap();
+
+        let token = deps.api.addr_make("token").to_string();
+        mock_adapter_queries(&mut deps.querier, vec![token.clone()], 0);
+
+        let simulate = |asset: Asset| -> SimulateBurnResponse {
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::SimulateBurn { asset }).unwrap()).unwrap()
+        };
+
+        // Native burns deposit and transfer to the burn auction
+        let res = simulate(Asset {
+            info: AssetInfo::NativeToken { denom: "inj".to_string() },
+            amount: Uint128::new(1_000),
+        });
+        assert!(res.allowed);
+        assert_eq!(res.error, None);
+        assert_eq!(res.converted_denom, None);
+        assert_eq!(res.auction_denom, Some("inj".to_string()));
+        assert_eq!(res.destination_subaccount, "0x1111111111111111111111111111111111111111111111111111111111111111");
+        assert_eq!(res.messages.len(), 2);
+        assert!(matches!(
+            &res.messages[1].msg,
+            CosmosMsg::Custom(InjectiveMsgWrapper { msg_data: InjectiveMsg::ExternalTransfer { .. }, .. })
+        ));
+
+        // CW20s go through the adapter first
+        let res = simulate(Asset {
+            info: AssetInfo::Token { contract_addr: token.clone() },
+            amount: Uint128::new(1_000),
+        });
+        let factory_denom = format!("factory/{}/{}", adapter, token);
+        assert!(res.allowed);
+        assert_eq!(res.converted_denom, Some(factory_denom.clone()));
+        assert_eq!(res.auction_denom, Some(factory_denom));
+        assert_eq!(res.messages.len(), 3);
+
+        // Validation errors come back as a result instead of a failed query
+        let res = simulate(Asset {
+            info: AssetInfo::NativeToken { denom: "inj".to_string() },
+            amount: Uint128::zero(),
+        });
+        assert!(!res.allowed);
+        assert_eq!(res.error, Some("Generic error: Burn amount must be greater than zero".to_string()));
+        assert!(res.messages.is_empty());
+
+        let res = simulate(Asset {
+            info: AssetInfo::Token { contract_addr: "not a token".to_string() },
+            amount: Uint128::new(1_000),
+        });
+        assert!(!res.allowed);
+        assert!(res.error.is_some());
+    }
+
+    #[test]
+    fn test_message_backends_encode_same_semantics() {
+        let sender = Addr::unchecked("inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8");
+        let source = SubaccountId::unchecked("0xfa918d0c7aee6645d2936b6d62f9d4a3deb742ce000000000000000000000001");
+        let destination = SubaccountId::unchecked("0x1111111111111111111111111111111111111111111111111111111111111111");
+        let amount = Coin {
+            denom: "inj".to_string(),
+            amount: 1_000u128.into(),
+        };
+        let proto_amount = Some(ProtoCoin {
+            denom: "inj".to_string(),
+            amount: "1000".to_string(),
+        });
+        let decode_any = |msg: CosmosMsg<InjectiveMsgWrapper>| match msg {
+            CosmosMsg::Any(any) => (any.type_url, any.value),
+            msg => panic!("Unexpected message: {:?}", msg),
+        };
+
+        // Deposit
+        let custom = MessageBackend::Custom.deposit(&sender, &source, amount.clone());
+        assert_eq!(
+            custom,
+            CosmosMsg::Custom(InjectiveMsgWrapper {
+                route: InjectiveRoute::Exchange,
+                msg_data: InjectiveMsg::Deposit {
+                    sender: sender.clone(),
+                    subaccount_id: source.clone(),
+                    amount: amount.clone(),
+                },
+            })
+        );
+        let (type_url, value) = decode_any(MessageBackend::Protobuf.deposit(&sender, &source, amount.clone()));
+        assert_eq!(type_url, "/injective.exchange.v1beta1.MsgDeposit");
+        assert_eq!(
+            MsgDeposit::try_from(value).unwrap(),
+            MsgDeposit {
+                sender: sender.to_string(),
+                subaccount_id: source.as_str().to_string(),
+                amount: proto_amount.clone(),
+            }
+        );
+
+        // Withdraw
+        let custom = MessageBackend::Custom.withdraw(&sender, &source, amount.clone());
+        assert_eq!(
+            custom,
+            CosmosMsg::Custom(InjectiveMsgWrapper {
+                route: InjectiveRoute::Exchange,
+                msg_data: InjectiveMsg::Withdraw {
+                    sender: sender.clone(),
+                    subaccount_id: source.clone(),
+                    amount: amount.clone(),
+                },
+            })
+        );
+        let (type_url, value) = decode_any(MessageBackend::Protobuf.withdraw(&sender, &source, amount.clone()));
+        assert_eq!(type_url, "/injective.exchange.v1beta1.MsgWithdraw");
+        assert_eq!(
+            MsgWithdraw::try_from(value).unwrap(),
+            MsgWithdraw {
+                sender: sender.to_string(),
+                subaccount_id: source.as_str().to_string(),
+                amount: proto_amount.clone(),
+            }
+        );
+
+        // ExternalTransfer
+        let custom = MessageBackend::Custom.external_transfer(&sender, &source, &destination, amount.clone());
+        assert_eq!(
+            custom,
+            CosmosMsg::Custom(InjectiveMsgWrapper {
+                route: InjectiveRoute::Exchange,
+                msg_data: InjectiveMsg::ExternalTransfer {
+                    sender: sender.clone(),
+                    source_subaccount_id: source.clone(),
+                    destination_subaccount_id: destination.clone(),
+                    amount: amount.clone(),
+                },
+            })
+        );
+        let (type_url, value) =
+            decode_any(MessageBackend::Protobuf.external_transfer(&sender, &source, &destination, amount));
+        assert_eq!(type_url, "/injective.exchange.v1beta1.MsgExternalTransfer");
+        assert_eq!(
+            MsgExternalTransfer::try_from(value).unwrap(),
+            MsgExternalTransfer {
+                sender: sender.to_string(),
+                source_subaccount_id: source.as_str().to_string(),
+                destination_subaccount_id: destination.as_str().to_string(),
+                amount: proto_amount,
+            }
+        );
+    }
+
+    #[test]
+    fn test_subaccount_routing() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked("inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8");
+        let admin = "inj1q2m26a7jdzjyfdn545vqsude3zwwtfrdap5jgz";
+        let admin_info = MessageInfo {
+            sender: Addr::unchecked(admin),
+            funds: vec![],
+        };
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            InstantiateMsg {
+                admin: admin.to_string(),
+                adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+                burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+            },
+        )
+        .unwrap();
+        let query_routing = |deps: Deps| -> SubaccountRoutingResponse {
+            from_json(query(deps, env.clone(), QueryMsg::GetSubaccountRouting {}).unwrap()).unwrap()
+        };
+        let burn = |deps: DepsMut| {
+            let info = MessageInfo {
+                sender: Addr::unchecked(admin),
+                funds: vec![Coin::new(500u128, "inj")],
+            };
+            let msg = ExecuteMsg::SendNative {
+                asset: Asset {
+                    info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                    amount: 500u128.into(),
+                },
+                callback: None,
+                referrer: None,
+                drive_id: None,
+                idempotency_key: None,
+                deadline: None,
+            };
+            execute(deps, env.clone(), info, msg).unwrap()
+        };
+
+        // Nonce 1 intermediate subaccount by default
+        assert_eq!(
+            query_routing(deps.as_ref()),
+            SubaccountRoutingResponse {
+                routing: SubaccountRouting::Intermediate { nonce: 1 },
+                subaccount_id: "0xfa9181a47aee67222e926b6de2cbcea31eb742ce000000000000000000000001".to_string(),
+            }
+        );
+
+        // Only the admin can change routing, and nonce 0 is reserved for direct mode
+        let set_routing = |routing| ExecuteMsg::SetSubaccountRouting { routing };
+        let stranger = MessageInfo {
+            sender: Addr::unchecked("inj1stranger"),
+            funds: vec![],
+        };
+        execute(deps.as_mut(), env.clone(), stranger, set_routing(SubaccountRouting::Direct {})).unwrap_err();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            set_routing(SubaccountRouting::Intermediate { nonce: 0 }),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("nonce must be greater than zero"));
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            set_routing(SubaccountRouting::Intermediate { nonce: 7 }),
+        )
+        .unwrap();
+        let nonce_seven = "0xfa9181a47aee67222e926b6de2cbcea31eb742ce000000000000000000000007";
+        assert_eq!(query_routing(deps.as_ref()).subaccount_id, nonce_seven);
+        let res = burn(deps.as_mut());
+        assert_eq!(res.messages.len(), 2);
+        match &res.messages[0].msg {
+            CosmosMsg::Custom(InjectiveMsgWrapper {
+                msg_data: InjectiveMsg::Deposit { subaccount_id, .. },
+                ..
+            }) => assert_eq!(subaccount_id.as_str(), nonce_seven),
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+
+        // Direct mode transfers straight out of the default subaccount
+        execute(deps.as_mut(), env.clone(), admin_info, set_routing(SubaccountRouting::Direct {})).unwrap();
+        let default_subaccount = "0xfa9181a47aee67222e926b6de2cbcea31eb742ce000000000000000000000000";
+        assert_eq!(
+            query_routing(deps.as_ref()),
+            SubaccountRoutingResponse {
+                routing: SubaccountRouting::Direct {},
+                subaccount_id: default_subaccount.to_string(),
+            }
+        );
+        let res = burn(deps.as_mut());
+        assert_eq!(res.messages.len(), 1);
+        match &res.messages[0].msg {
+            CosmosMsg::Custom(InjectiveMsgWrapper {
+                msg_data: InjectiveMsg::ExternalTransfer { source_subaccount_id, .. },
+                ..
+            }) => assert_eq!(source_subaccount_id.as_str(), default_subaccount),
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn test_burn_stream_vests_over_time() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked("inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8");
+        let admin = "inj1q2m26a7jdzjyfdn545vqsude3zwwtfrdap5jgz";
+        let treasury = Addr::unchecked("inj1treasury");
+        let keeper = MessageInfo {
+            sender: Addr::unchecked("inj1keeper"),
+            funds: vec![],
+        };
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            MessageInfo {
+                sender: Addr::unchecked(admin),
+                funds: vec![],
+            },
+            InstantiateMsg {
+                admin: admin.to_string(),
+                adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+                burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+            },
+        )
+        .unwrap();
+
+        // 1000 inj over 1000 seconds, released in steps of 100 seconds
+        let start = env.block.time.plus_seconds(100);
+        let schedule = StreamSchedule {
+            start_time: start,
+            end_time: start.plus_seconds(1_000),
+            interval: 100,
+        };
+        let asset = Asset {
+            info: AssetInfo::NativeToken { denom: "inj".to_string() },
+            amount: 1_000u128.into(),
+        };
+        let create = |deps: DepsMut, schedule: StreamSchedule, funds: u128| {
+            let info = MessageInfo {
+                sender: treasury.clone(),
+                funds: vec![Coin::new(funds, "inj")],
+            };
+            execute(deps, env.clone(), info, ExecuteMsg::CreateStream { asset: asset.clone(), schedule })
+        };
+        let err = create(deps.as_mut(), StreamSchedule { interval: 0, ..schedule.clone() }, 1_000).unwrap_err();
+        assert!(err.to_string().contains("Stream interval must be between 1 and 1000 seconds"));
+        create(deps.as_mut(), schedule.clone(), 999).unwrap_err();
+        create(deps.as_mut(), schedule, 1_000).unwrap();
+
+        let query_stream = |deps: Deps, env: &Env| -> StreamResponse {
+            from_json(query(deps, env.clone(), QueryMsg::GetStream { id: 1 }).unwrap()).unwrap()
+        };
+        let release = |deps: DepsMut, env: &Env| {
+            execute(deps, env.clone(), keeper.clone(), ExecuteMsg::ReleaseStream { id: 1 })
+        };
+
+        // Nothing vests before the start
+        let err = release(deps.as_mut(), &env).unwrap_err();
+        assert!(err.to_string().contains("Nothing to release from stream 1 yet"));
+
+        // Two and a half intervals in, two intervals have vested
+        env.block.time = start.plus_seconds(250);
+        let stream = query_stream(deps.as_ref(), &env);
+        assert_eq!(stream.vested, Uint128::new(200));
+        assert_eq!(stream.releasable, Uint128::new(200));
+        let res = release(deps.as_mut(), &env).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        match &res.messages[1].msg {
+            CosmosMsg::Custom(InjectiveMsgWrapper {
+                msg_data: InjectiveMsg::ExternalTransfer { amount, .. },
+                ..
+            }) => assert_eq!(amount, &Coin::new(200u128, "inj")),
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+        release(deps.as_mut(), &env).unwrap_err();
+        assert_eq!(query_stream(deps.as_ref(), &env).stream.released, Uint128::new(200));
+
+        // Only the owner can cancel, the vested part still burns and the rest is refunded
+        env.block.time = start.plus_seconds(500);
+        execute(deps.as_mut(), env.clone(), keeper.clone(), ExecuteMsg::CancelStream { id: 1 }).unwrap_err();
+        let owner = MessageInfo {
+            sender: treasury.clone(),
+            funds: vec![],
+        };
+        let res = execute(deps.as_mut(), env.clone(), owner, ExecuteMsg::CancelStream { id: 1 }).unwrap();
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: treasury.to_string(),
+                amount: vec![Coin::new(500u128, "inj")],
+            })
+        );
+        let stream = query_stream(deps.as_ref(), &env);
+        assert_eq!(stream.stream.status, StreamStatus::Cancelled);
+        assert_eq!(stream.stream.released, Uint128::new(500));
+        assert_eq!(stream.stream.refunded, Uint128::new(500));
+        assert_eq!(stream.releasable, Uint128::zero());
+
+        // A cancelled stream does not keep vesting
+        env.block.time = start.plus_seconds(2_000);
+        assert_eq!(query_stream(deps.as_ref(), &env).vested, Uint128::new(500));
+        release(deps.as_mut(), &env).unwrap_err();
+    }
+
+    #[test]
+    fn test_rate_limit_per_sender() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked("inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8");
+        let admin = MessageInfo {
+            sender: Addr::unchecked("inj1q2m26a7jdzjyfdn545vqsude3zwwtfrdap5jgz"),
+            funds: vec![],
+        };
+        let bot = deps.api.addr_make("bot");
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            InstantiateMsg {
+                admin: admin.sender.to_string(),
+                adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+                burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+            },
+        )
+        .unwrap();
+        let limit = RateLimit {
+            window_blocks: 10,
+            max_burns: Some(2),
+            max_amounts: vec![Asset {
+                info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                amount: 500u128.into(),
+            }],
+        };
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::SetRateLimit { limit: Some(limit) }).unwrap();
+
+        let burn = |deps: DepsMut, env: &Env, amount: u128| {
+            let info = MessageInfo {
+                sender: bot.clone(),
+                funds: vec![Coin::new(amount, "inj")],
+            };
+            let asset = Asset {
+                info: AssetInfo::NativeToken { denom: "inj".to_string() },
+                amount: amount.into(),
+            };
+            execute(deps, env.clone(), info, ExecuteMsg::SendNative { asset, callback: None, referrer: None, drive_id: None, idempotency_key: None, deadline: None })
+        };
+        let start = env.block.height;
+
+        // The third burn within ten blocks waits for the first to leave the window
+        burn(deps.as_mut(), &env, 100).unwrap();
+        env.block.height = start + 1;
+        burn(deps.as_mut(), &env, 100).unwrap();
+        env.block.height = start + 2;
+        let err = burn(deps.as_mut(), &env, 100).unwrap_err();
+        assert!(matches!(err, ContractError::RateLimited { retry_at_height, .. } if retry_at_height == start + 10));
+        assert_eq!(
+            err.to_string(),
+            format!("Sender {} is rate limited, try again at height {}", bot, start + 10)
+        );
+        env.block.height = start + 10;
+        burn(deps.as_mut(), &env, 300).unwrap();
+
+        // 550 inj would be in the window, the sender has to wait for the 300 to expire
+        env.block.height = start + 11;
+        let err = burn(deps.as_mut(), &env, 250).unwrap_err();
+        assert!(matches!(err, ContractError::RateLimited { retry_at_height, .. } if retry_at_height == start + 20));
+        let err = burn(deps.as_mut(), &env, 600).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Burning 600inj exceeds the limit of 500inj per 10 blocks");
+        let usage: SenderRateLimitResponse = from_json(
+            query(deps.as_ref(), env.clone(), QueryMsg::GetSenderRateLimit { sender: bot.to_string(), token: None }).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(usage.burns, 1);
+        assert_eq!(usage.amounts[0].amount, Uint128::new(300));
+
+        // Any CW20 can name the bot as sender, it only fills the window of burns it relays itself
+        let junk = deps.api.addr_make("junk");
+        mock_adapter_queries(&mut deps.querier, vec![junk.to_string()], 0);
+        let junk_burn = |deps: DepsMut, env: &Env| {
+            let info = MessageInfo { sender: junk.clone(), funds: vec![] };
+            let receive = Cw20ReceiveMsg { sender: bot.to_string(), amount: 1u128.into(), msg: Binary::default() };
+            execute(deps, env.clone(), info, ExecuteMsg::Receive(receive))
+        };
+        junk_burn(deps.as_mut(), &env).unwrap();
+        junk_burn(deps.as_mut(), &env).unwrap();
+        let err = junk_burn(deps.as_mut(), &env).unwrap_err();
+        assert!(matches!(err, ContractError::RateLimited { retry_at_height, .. } if retry_at_height == start + 21));
+        let relayed: SenderRateLimitResponse = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GetSenderRateLimit { sender: bot.to_string(), token: Some(junk.to_string()) },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(relayed.burns, 2);
+        burn(deps.as_mut(), &env, 100).unwrap();
+
+        // Blocks that left the window are dropped, nothing is kept once the window is empty
+        env.block.height = start + 30;
+        let limit = RateLimit { window_blocks: 10, max_burns: None, max_amounts: vec![] };
+        execute(deps.as_mut(), env.clone(), admin.clone(), ExecuteMsg::SetRateLimit { limit: Some(limit) }).unwrap();
+        burn(deps.as_mut(), &env, 100).unwrap();
+        assert!(!SENDER_ACTIVITY.has(&deps.storage, (&bot, "")));
+
+        // Approved high-volume senders are not limited
+        let exempt = ExecuteMsg::SetRateLimitExemption { address: bot.to_string(), exempt: true };
+        execute(deps.as_mut(), env.clone(), admin, exempt).unwrap();
+        assert!(!SENDER_ACTIVITY.has(&deps.storage, (&bot, junk.as_str())));
+        burn(deps.as_mut(), &env, 250).unwrap();
+        burn(deps.as_mut(), &env, 250).unwrap();
+        let usage: SenderRateLimitResponse = from_json(
+            query(deps.as_ref(), env.clone(), QueryMsg::GetSenderRateLimit { sender: bot.to_string(), token: None }).unwrap(),
+        )
+        .unwrap();
+        assert!(usage.exempt);
+        assert_eq!(usage.burns, 0);
+    }
+
+    #[test]
+    fn test_burn_permit_signature_vector() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked("inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8");
+        let admin = MessageInfo {
+            sender: Addr::unchecked("inj1q2m26a7jdzjyfdn545vqsude3zwwtfrdap5jgz"),
+            funds: vec![],
+        };
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            admin.clone(),
+            InstantiateMsg {
+                admin: admin.sender.to_string(),
+                adapter_contract: "inj14ejqjyq8um4p3xfqj74yld5waqljf88f9eneuk".to_string(),
+                burn_auction_subaccount: "0x1111111111111111111111111111111111111111111111111111111111111111".to_string(),
+            },
+        )
+        .unwrap();
+        let token = deps.api.addr_make("token");
+        mock_adapter_queries(&mut deps.querier, vec![token.to_string()], 0);
+
+        // Signed with the secret key 1 by the signing helper of the multitests
+        let permit = BurnPermit {
+            token: token.to_string(),
+            amount: 500u128.into(),
+            nonce: 0,
+            expiry: BlockPoint::AtHeight { height: 12_400 },
+            chain_id: "cosmos-testnet-14002".to_string(),
+            contract_addr: "inj1l2gcrfr6aenjyt5jddk79j7w5v0twskw6n70y8".to_string(),
+        };
+        let public_key = Binary::from(hex::decode("0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8").unwrap());
+        let signature = Binary::from(hex::decode("11d4d9dfbc11a6cb2ab7f841d53a4c87a8e86c53416d1c4f5478f42132cffeba58d72bf3ba332ae44df2d3b3d1f887c14c52b3e3e381591781f000997fe5e6ec").unwrap());
+        assert_eq!(
+            hex::encode(permit_digest(&permit).unwrap()),
+            "f0fd69b645f6f1df96cbef440146c00de9e6267dbb390c4ff93faf8d2722dc1c"
+        );
+
+        // The owner is the account of the well-known Ethereum address of that key
+        let owner = permit_owner(deps.as_ref(), &public_key).unwrap();
+        assert_eq!(
+            hex::encode(deps.api.addr_canonicalize(owner.as_str()).unwrap().as_slice()),
+            "7e5f4552091a69125d5dfcb7b8c2659029395bdf"
+        );
+
+        let relayer = MessageInfo {
+            sender: deps.api.addr_make("relayer"),
+            funds: vec![],
+        };
+        let msg = ExecuteMsg::BurnWithPermit {
+            permit: permit.clone(),
+            signature: signature.clone(),
+            public_key: public_key.clone(),
+        };
+        // Coins a relayer attaches are refused rather than left in the contract
+        let paying_relayer = MessageInfo {
+            sender: relayer.sender.clone(),
+            funds: vec![Coin::new(10u128, "inj")],
+        };
+        let err = execute(deps.as_mut(), env.clone(), paying_relayer, msg.clone()).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Unexpected funds provided: 10inj");
+
+        let res = execute(deps.as_mut(), env.clone(), relayer.clone(), msg.clone()).unwrap();
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute { contract_addr, msg, .. }) => {
+                assert_eq!(*contract_addr, token.to_string());
+                let msg: cw20::Cw20ExecuteMsg = from_json(msg).unwrap();
+                assert_eq!(
+                    msg,
+                    cw20::Cw20ExecuteMsg::TransferFrom {
+                        owner: owner.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount: 500u128.into(),
+                    }
+                );
+            }
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+        let nonce: PermitNonceResponse =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetPermitNonce { owner: owner.to_string() }).unwrap())
+                .unwrap();
+        assert_eq!(nonce.nonce, 1);
+
+        let err = execute(deps.as_mut(), env.clone(), relayer.clone(), msg).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Invalid permit nonce 0, expected 1");
+
+        // Compressed keys carry no Ethereum address and are refused
+        let msg = ExecuteMsg::BurnWithPermit {
+            permit,
+            signature,
+            public_key: Binary::from(&public_key[..33]),
+        };
+        let err = execute(deps.as_mut(), env, relayer, msg).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Generic error: Permit public key must be an uncompressed 65 byte secp256k1 key"
+        );
+    }
 }